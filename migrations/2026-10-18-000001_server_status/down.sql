@@ -0,0 +1,3 @@
+alter table public.servers add column started boolean;
+update public.servers set started = status in ('starting', 'running', 'stopping');
+alter table public.servers drop column status;
@@ -0,0 +1,5 @@
+alter table public.servers
+  add column status text not null default 'stopped'
+  check (status in ('stopped', 'starting', 'running', 'stopping', 'crashed', 'deleting'));
+update public.servers set status = 'running' where started;
+alter table public.servers drop column started;
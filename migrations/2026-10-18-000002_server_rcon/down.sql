@@ -0,0 +1,3 @@
+alter table public.servers
+  drop column rcon_port,
+  drop column rcon_password;
@@ -0,0 +1,3 @@
+alter table public.servers
+  add column rcon_port BigInt,
+  add column rcon_password text;
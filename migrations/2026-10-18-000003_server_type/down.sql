@@ -0,0 +1 @@
+alter table public.servers drop column server_type;
@@ -0,0 +1,3 @@
+alter table public.servers
+  add column server_type text not null default 'vanilla'
+  check (server_type in ('vanilla', 'paper', 'purpur', 'spigot', 'fabric', 'forge', 'neoforge'));
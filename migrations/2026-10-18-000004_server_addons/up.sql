@@ -0,0 +1,8 @@
+create table public.server_addons (
+  id BIGSERIAL primary key not null,
+  server_id BigInt not null references public.servers (id) on delete cascade,
+  kind text not null check (kind in ('mod', 'plugin')),
+  source text not null check (source in ('modrinth', 'url', 'file')),
+  value text not null,
+  unique (server_id, kind, value)
+);
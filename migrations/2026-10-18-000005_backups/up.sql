@@ -0,0 +1,10 @@
+create table public.backups (
+  id BIGSERIAL primary key not null,
+  server_id BigInt not null references public.servers (id) on delete cascade,
+  file_name text not null,
+  size BigInt not null,
+  kind text not null check (kind in ('scheduled', 'manual')),
+  created_at timestamptz not null default now()
+);
+
+create index backups_server_id_created_at on public.backups (server_id, created_at desc);
@@ -0,0 +1,5 @@
+DROP TABLE backup_restores;
+
+DELETE FROM backups WHERE kind = 'safety';
+ALTER TABLE backups DROP CONSTRAINT backups_kind_check;
+ALTER TABLE backups ADD CONSTRAINT backups_kind_check CHECK (kind IN ('scheduled', 'manual'));
@@ -0,0 +1,11 @@
+alter table public.backups drop constraint backups_kind_check;
+alter table public.backups add constraint backups_kind_check check (kind in ('scheduled', 'manual', 'safety'));
+
+create table public.backup_restores (
+  id BIGSERIAL primary key not null,
+  server_id BigInt not null references public.servers (id) on delete cascade,
+  backup_id BigInt references public.backups (id) on delete set null,
+  safety_backup_id BigInt references public.backups (id) on delete set null,
+  user_id BigInt not null,
+  restored_at timestamptz not null default now()
+);
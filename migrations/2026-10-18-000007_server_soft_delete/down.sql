@@ -0,0 +1,6 @@
+DELETE FROM servers WHERE deleted_at IS NOT NULL;
+
+DROP INDEX servers_name_active;
+ALTER TABLE servers ADD CONSTRAINT servers_name_key UNIQUE (name);
+
+ALTER TABLE servers DROP COLUMN deleted_at;
@@ -0,0 +1,4 @@
+alter table public.servers add column deleted_at timestamptz;
+
+alter table public.servers drop constraint servers_name_key;
+create unique index servers_name_active on public.servers (name) where deleted_at is null;
@@ -0,0 +1,3 @@
+DROP TABLE server_members;
+
+ALTER TABLE servers DROP COLUMN owner_id;
@@ -0,0 +1,8 @@
+alter table public.servers add column owner_id BigInt;
+
+create table public.server_members (
+  server_id BigInt not null references public.servers (id) on delete cascade,
+  user_id BigInt not null,
+  role text not null check (role in ('owner', 'operator', 'player')),
+  primary key (server_id, user_id)
+);
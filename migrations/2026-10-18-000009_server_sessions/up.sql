@@ -0,0 +1,8 @@
+create table public.server_sessions (
+  id BIGSERIAL primary key not null,
+  server_id BigInt not null references public.servers (id) on delete cascade,
+  started_at timestamptz not null default now(),
+  stopped_at timestamptz
+);
+
+create index server_sessions_server_id on public.server_sessions (server_id, started_at);
@@ -0,0 +1,14 @@
+create table public.audit_events (
+  id BIGSERIAL primary key not null,
+  user_id BigInt not null,
+  action text not null,
+  server_name text,
+  parameters text not null default '',
+  success boolean not null,
+  error text,
+  created_at timestamptz not null default now()
+);
+
+create index audit_events_created_at on public.audit_events (created_at);
+create index audit_events_server_name on public.audit_events (server_name, created_at);
+create index audit_events_user_id on public.audit_events (user_id, created_at);
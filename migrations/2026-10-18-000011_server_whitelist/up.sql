@@ -0,0 +1,7 @@
+create table public.server_whitelist (
+  id BIGSERIAL primary key not null,
+  server_id BigInt not null references public.servers (id) on delete cascade,
+  player text not null
+);
+
+create unique index server_whitelist_player on public.server_whitelist (server_id, lower(player));
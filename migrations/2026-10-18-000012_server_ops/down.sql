@@ -0,0 +1,3 @@
+DROP TABLE server_ops;
+
+DROP TABLE minecraft_accounts;
@@ -0,0 +1,12 @@
+create table public.minecraft_accounts (
+  user_id BigInt primary key not null,
+  player text not null
+);
+
+create table public.server_ops (
+  id BIGSERIAL primary key not null,
+  server_id BigInt not null references public.servers (id) on delete cascade,
+  player text not null
+);
+
+create unique index server_ops_player on public.server_ops (server_id, lower(player));
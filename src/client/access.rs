@@ -0,0 +1,80 @@
+use crate::client::data::CommandAccessData;
+use crate::client::error::ClientError;
+use crate::server::permission::is_admin;
+use serenity::all::{Context, CreateCommand, Member, Permissions, RoleId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Discord roles allowed to use each command, read from `COMMAND_ROLES` formatted as
+// `create=111,222;delete=111`. Commands left out of it are open to everyone.
+#[derive(Debug, Default)]
+pub struct CommandAccess {
+    roles: HashMap<String, Vec<RoleId>>,
+}
+
+impl CommandAccess {
+    pub fn from_env() -> Result<Self, ClientError> {
+        let Ok(value) = std::env::var("COMMAND_ROLES") else {
+            return Ok(Self::default());
+        };
+
+        let mut roles: HashMap<String, Vec<RoleId>> = HashMap::new();
+        for entry in value
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (command, ids) = entry.split_once('=').ok_or_else(|| {
+                ClientError::Other(format!("Invalid `COMMAND_ROLES` entry `{entry}`."))
+            })?;
+            for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                let id = id.parse::<u64>().map_err(|error| {
+                    ClientError::Other(format!("Invalid role `{id}` in `COMMAND_ROLES`: {error}"))
+                })?;
+                roles
+                    .entry(command.trim().to_owned())
+                    .or_default()
+                    .push(RoleId::new(id));
+            }
+        }
+
+        Ok(Self { roles })
+    }
+
+    pub fn check(&self, command: &str, member: Option<&Member>) -> Result<(), ClientError> {
+        let Some(roles) = self.roles.get(command) else {
+            return Ok(());
+        };
+
+        let has_role =
+            member.is_some_and(|member| member.roles.iter().any(|role| roles.contains(role)));
+        if has_role || is_admin(member)? {
+            return Ok(());
+        }
+
+        Err(ClientError::OtherStatic(
+            "Vous n'avez pas le rôle requis pour cette commande.",
+        ))
+    }
+
+    // Discord cannot restrict a command to roles by default, so restricted commands are only
+    // shown to administrators until the roles are granted in the integration settings.
+    pub fn restrict(&self, name: &str, command: CreateCommand) -> CreateCommand {
+        if self.roles.contains_key(name) {
+            command.default_member_permissions(Permissions::ADMINISTRATOR)
+        } else {
+            command
+        }
+    }
+}
+
+pub async fn get_access_from_ctx(ctx: &Context) -> Result<Arc<CommandAccess>, ClientError> {
+    ctx.data
+        .read()
+        .await
+        .get::<CommandAccessData>()
+        .ok_or(ClientError::OtherStatic(
+            "Missing CommandAccessData in data.",
+        ))
+        .cloned()
+}
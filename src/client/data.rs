@@ -1,8 +1,30 @@
+use crate::client::access::CommandAccess;
 use crate::database::postgresql::PgPool;
+use crate::runtime::ContainerRuntime;
+use crate::server::lock::ServerLocks;
 use serenity::prelude::TypeMapKey;
+use std::sync::Arc;
 
 pub struct PgPoolData;
 
 impl TypeMapKey for PgPoolData {
     type Value = PgPool;
 }
+
+pub struct RuntimeData;
+
+impl TypeMapKey for RuntimeData {
+    type Value = Arc<dyn ContainerRuntime>;
+}
+
+pub struct ServerLocksData;
+
+impl TypeMapKey for ServerLocksData {
+    type Value = Arc<ServerLocks>;
+}
+
+pub struct CommandAccessData;
+
+impl TypeMapKey for CommandAccessData {
+    type Value = Arc<CommandAccess>;
+}
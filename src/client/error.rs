@@ -16,6 +16,9 @@ pub enum ClientError {
     YmlError(String),
     IoError(String),
     JsonError(String),
+    RuntimeError(String),
+    ProtocolError(String),
+    StartError(String, String),
     OtherStatic(&'static str),
     Other(String),
 }
@@ -34,6 +37,12 @@ impl fmt::Display for ClientError {
             Self::YmlError(error) => write!(f, "**yml conversion erreur : {error}**"),
             Self::IoError(error) => write!(f, "**file erreur : {error}**"),
             Self::JsonError(error) => write!(f, "**parse json file ereur : {error}**"),
+            Self::RuntimeError(error) => write!(f, "**docker erreur : {error}**"),
+            Self::ProtocolError(error) => write!(f, "**protocole erreur : {error}**"),
+            Self::StartError(error, logs) => write!(
+                f,
+                "**Le serveur n'a pas démarré :**\n{error}\n**Derniers logs :**\n```\n{logs}\n```"
+            ),
             Self::OtherStatic(error) => write!(f, "**Erreur : {error}**"),
             Self::Other(error) => write!(f, "**Erreur : {error}**"),
         }
@@ -1,13 +1,17 @@
+pub mod access;
 pub mod data;
 pub mod error;
 mod serenity_handler;
 
-use crate::client::data::PgPoolData;
+use crate::client::access::CommandAccess;
+use crate::client::data::{CommandAccessData, PgPoolData, RuntimeData, ServerLocksData};
 use crate::client::serenity_handler::SerenityHandler;
 use crate::database::postgresql::get_pool;
+use crate::runtime;
 use crate::util::parse_key;
 use serenity::prelude::GatewayIntents;
 use std::error::Error;
+use std::sync::Arc;
 
 pub struct Client {
     client: serenity::Client,
@@ -24,11 +28,13 @@ impl Client {
                 .application_id(parse_key::<u64>("DISCORD_APP_ID")?.into())
                 .await?;
 
-        client
-            .data
-            .write()
-            .await
-            .insert::<PgPoolData>(get_pool().await);
+        {
+            let mut data = client.data.write().await;
+            data.insert::<PgPoolData>(get_pool().await);
+            data.insert::<RuntimeData>(runtime::from_env()?);
+            data.insert::<ServerLocksData>(Arc::default());
+            data.insert::<CommandAccessData>(Arc::new(CommandAccess::from_env()?));
+        }
 
         Ok(Self { client })
     }
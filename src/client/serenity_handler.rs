@@ -1,9 +1,16 @@
+use crate::client::access::get_access_from_ctx;
 use crate::client::error::ClientError;
 use crate::commands;
+use crate::interarction::autocomplete_backup::autocomplete_backup;
 use crate::interarction::autocomplete_version::autocomplete_version;
+use crate::interarction::button_audit::button_audit;
 use crate::interarction::button_list::button_list;
+use crate::interarction::confirm::button_confirm;
+use crate::server::addons::AddonKind;
+use crate::server::audit::{AuditEntry, record};
+use crate::tasks;
 use crate::util::{EMBED_COLOR, parse_key};
-use serenity::all::{CreateEmbed, CreateInteractionResponseMessage};
+use serenity::all::{CreateEmbed, CreateInteractionResponseMessage, EditInteractionResponse};
 use serenity::{
     async_trait,
     client::{Context, EventHandler},
@@ -18,62 +25,129 @@ impl EventHandler for SerenityHandler {
 
         let guild_id = GuildId::new(parse_key::<u64>("DISCORD_GUILD_ID").unwrap());
 
-        let result = guild_id
-            .set_commands(
-                &ctx.http,
-                vec![
-                    commands::create::register(),
-                    commands::list::register(),
-                    commands::delete::register(),
-                    commands::start::register(),
-                    commands::stop::register(),
-                ],
-            )
-            .await;
+        let access = match get_access_from_ctx(&ctx).await {
+            Ok(access) => access,
+            Err(error) => {
+                log::error!("{error}. Bot shutdown...");
+                std::process::exit(1);
+            }
+        };
+        let commands = [
+            ("create", commands::create::register()),
+            ("import", commands::import::register()),
+            ("export", commands::export::register()),
+            ("backup", commands::backup::register()),
+            ("list", commands::list::register()),
+            ("delete", commands::delete::register()),
+            ("undelete", commands::undelete::register()),
+            ("start", commands::start::register()),
+            ("stop", commands::stop::register()),
+            ("restart", commands::restart::register()),
+            ("console", commands::console::register()),
+            ("logs", commands::logs::register()),
+            ("members", commands::members::register()),
+            ("quota", commands::quota::register()),
+            ("audit", commands::audit::register()),
+            ("whitelist", commands::whitelist::register()),
+            ("op", commands::op::register()),
+            ("link", commands::link::register()),
+            ("mods", commands::addons::register(AddonKind::Mod)),
+            ("plugins", commands::addons::register(AddonKind::Plugin)),
+        ]
+        .into_iter()
+        .map(|(name, command)| access.restrict(name, command))
+        .collect();
+
+        let result = guild_id.set_commands(&ctx.http, commands).await;
         if let Err(error) = result {
             log::error!("Unable to register commands: {error}. Bot shutdown...");
             std::process::exit(1);
         }
+
+        tasks::spawn_all(&ctx);
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         if let Interaction::Command(command) = interaction {
-            let res = match command.data.name.as_str() {
-                "create" => commands::create::run(&ctx, &command).await,
-                "list" => commands::list::run(&ctx, &command).await,
-                "delete" => commands::delete::run(&ctx, &command).await,
-                "start" => commands::start::run(&ctx, &command).await,
-                "stop" => commands::stop::run(&ctx, &command).await,
-                _ => Err(ClientError::OtherStatic(
-                    "Slash command defined at Discord but not in the bot.",
-                )),
+            let allowed = get_access_from_ctx(&ctx)
+                .await
+                .and_then(|access| access.check(&command.data.name, command.member.as_deref()));
+            let res = if let Err(error) = allowed {
+                Err(error)
+            } else {
+                match command.data.name.as_str() {
+                    "create" => commands::create::run(&ctx, &command).await,
+                    "import" => commands::import::run(&ctx, &command).await,
+                    "export" => commands::export::run(&ctx, &command).await,
+                    "backup" => commands::backup::run(&ctx, &command).await,
+                    "list" => commands::list::run(&ctx, &command).await,
+                    "delete" => commands::delete::run(&ctx, &command).await,
+                    "undelete" => commands::undelete::run(&ctx, &command).await,
+                    "start" => commands::start::run(&ctx, &command).await,
+                    "stop" => commands::stop::run(&ctx, &command).await,
+                    "restart" => commands::restart::run(&ctx, &command).await,
+                    "console" => commands::console::run(&ctx, &command).await,
+                    "logs" => commands::logs::run(&ctx, &command).await,
+                    "members" => commands::members::run(&ctx, &command).await,
+                    "quota" => commands::quota::run(&ctx, &command).await,
+                    "audit" => commands::audit::run(&ctx, &command).await,
+                    "whitelist" => commands::whitelist::run(&ctx, &command).await,
+                    "op" => commands::op::run(&ctx, &command).await,
+                    "link" => commands::link::run(&ctx, &command).await,
+                    "mods" => commands::addons::run(&ctx, &command, AddonKind::Mod).await,
+                    "plugins" => commands::addons::run(&ctx, &command, AddonKind::Plugin).await,
+                    _ => Err(ClientError::OtherStatic(
+                        "Slash command defined at Discord but not in the bot.",
+                    )),
+                }
             };
-            if let Err(error) = res {
+            if let Err(error) = &res {
                 let embed = CreateEmbed::new()
                     .description(error.to_string())
                     .color(EMBED_COLOR);
 
-                if let Err(err) = command
+                // The command may already have answered before failing (e.g. "Démarrage..."),
+                // in which case the error replaces that first response.
+                if command
                     .create_response(
                         &ctx.http,
                         serenity::builder::CreateInteractionResponse::Message(
-                            CreateInteractionResponseMessage::new().add_embed(embed),
+                            CreateInteractionResponseMessage::new().add_embed(embed.clone()),
                         ),
                     )
                     .await
+                    .is_err()
+                    && let Err(err) = command
+                        .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
+                        .await
                 {
                     log::error!("Cannot respond to slash command: {err}");
                 }
             }
+
+            record(
+                &ctx,
+                command.user.id,
+                &AuditEntry::from_command(&command),
+                res.as_ref().err(),
+            )
+            .await;
         } else if let Interaction::Component(component) = interaction {
             if component.data.custom_id.starts_with("page-") {
                 button_list(ctx, component).await;
+            } else if component.data.custom_id.starts_with("audit-") {
+                button_audit(ctx, component).await;
+            } else if component.data.custom_id.starts_with("confirm-") {
+                button_confirm(ctx, component).await;
             }
         } else if let Interaction::Autocomplete(command) = interaction {
-            if command.data.name == "create" {
-                if let Err(e) = autocomplete_version(ctx, command).await {
-                    log::error!("{e}");
-                }
+            let result = match command.data.name.as_str() {
+                "create" | "import" => autocomplete_version(ctx, command).await,
+                "backup" => autocomplete_backup(ctx, command).await,
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                log::error!("{e}");
             }
         }
     }
@@ -0,0 +1,375 @@
+use crate::client::error::ClientError;
+use crate::commands::{
+    extract_attachment_optional, extract_str, extract_str_optional, extract_subcommand,
+};
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::server_addons::dsl as addons_dsl;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::addons::{AddonKind, AddonSource, jar_path};
+use crate::server::permission::{MemberRole, require};
+use crate::server::software::ServerType;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx};
+use diesel::dsl::exists;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, delete, insert_into};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    Attachment, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, EditInteractionResponse, ResolvedOption,
+};
+use std::path::Path;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+const MAX_JAR_SIZE: u32 = 50 * 1024 * 1024;
+
+pub async fn run(
+    ctx: &Context,
+    command: &CommandInteraction,
+    kind: AddonKind,
+) -> Result<(), ClientError> {
+    let options = command.data.options();
+    let (subcommand, options) = extract_subcommand(&options)?;
+    let name = extract_str("name", &options)?.to_lowercase();
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let (id, server_type): (i64, ServerType) = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::server_type))
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+
+    if !kind.supported_by(server_type) {
+        return Err(ClientError::Other(format!(
+            "Les serveurs {} ne supportent pas les {}s.",
+            server_type.label(),
+            kind.as_str()
+        )));
+    }
+
+    if subcommand != "list" {
+        require(
+            &mut conn,
+            id,
+            command.user.id,
+            command.member.as_deref(),
+            MemberRole::Operator,
+        )
+        .await?;
+    }
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new(),
+            ),
+        )
+        .await?;
+
+    let description = match subcommand {
+        "add" => add(&mut conn, id, kind, &options).await?,
+        "remove" => remove(&mut conn, id, kind, &options).await?,
+        "list" => list(&mut conn, id, kind).await?,
+        _ => return Err(ClientError::OtherStatic("Sous-commande inconnue.")),
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!("{}s de ``{name}``", capitalize(kind.as_str())))
+        .description(description)
+        .color(EMBED_COLOR);
+
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().add_embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+async fn add(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    kind: AddonKind,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, ClientError> {
+    let project = extract_str_optional("project", options)?;
+    let file = extract_attachment_optional("file", options)?;
+
+    let (source, value) = match (project, file) {
+        (Some(project), None) if project.starts_with("https://") => (AddonSource::Url, project),
+        (Some(project), None) => {
+            let is_valid = project
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+            if !is_valid {
+                return Err(ClientError::Other(format!(
+                    "``{project}`` n'est ni un projet Modrinth ni une URL https."
+                )));
+            }
+            (AddonSource::Modrinth, project)
+        }
+        (None, Some(file)) => (AddonSource::File, file_name(file)?),
+        _ => {
+            return Err(ClientError::OtherStatic(
+                "Indiquez soit un projet, soit un fichier.",
+            ));
+        }
+    };
+
+    let already_added: bool = diesel::select(exists(
+        addons_dsl::server_addons
+            .filter(addons_dsl::server_id.eq(id))
+            .filter(addons_dsl::kind.eq(kind.as_str()))
+            .filter(addons_dsl::value.eq(value)),
+    ))
+    .get_result(conn)
+    .await?;
+    if already_added {
+        return Err(ClientError::Other(format!(
+            "``{value}`` est déjà ajouté à ce serveur."
+        )));
+    }
+
+    if let Some(file) = file {
+        download_jar(&file.url, &jar_path(id, kind, value)).await?;
+    }
+
+    insert_into(addons_dsl::server_addons)
+        .values((
+            addons_dsl::server_id.eq(id),
+            addons_dsl::kind.eq(kind.as_str()),
+            addons_dsl::source.eq(source.as_str()),
+            addons_dsl::value.eq(value),
+        ))
+        .execute(conn)
+        .await?;
+
+    log::info!("Added {} {value} to server {id}", kind.as_str());
+
+    Ok(format!(
+        "**``{value}`` ajouté, il sera installé au prochain démarrage.**"
+    ))
+}
+
+async fn remove(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    kind: AddonKind,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, ClientError> {
+    let value = extract_str("project", options)?;
+
+    let source: String = delete(
+        addons_dsl::server_addons
+            .filter(addons_dsl::server_id.eq(id))
+            .filter(addons_dsl::kind.eq(kind.as_str()))
+            .filter(addons_dsl::value.eq(value)),
+    )
+    .returning(addons_dsl::source)
+    .get_result(conn)
+    .await
+    .optional()?
+    .ok_or_else(|| ClientError::Other(format!("``{value}`` n'est pas sur ce serveur.")))?;
+
+    if source == AddonSource::File.as_str() {
+        match fs::remove_file(jar_path(id, kind, value)).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                return Err(error.into());
+            }
+            _ => {}
+        }
+    }
+
+    log::info!("Removed {} {value} from server {id}", kind.as_str());
+
+    Ok(format!(
+        "**``{value}`` retiré, il sera désinstallé au prochain démarrage.**"
+    ))
+}
+
+async fn list(conn: &mut PgPooled<'_>, id: i64, kind: AddonKind) -> Result<String, ClientError> {
+    let addons: Vec<(String, String)> = addons_dsl::server_addons
+        .select((addons_dsl::source, addons_dsl::value))
+        .filter(addons_dsl::server_id.eq(id))
+        .filter(addons_dsl::kind.eq(kind.as_str()))
+        .order_by(addons_dsl::value)
+        .load(conn)
+        .await?;
+
+    if addons.is_empty() {
+        return Ok(format!("Aucun {} installé.", kind.as_str()));
+    }
+
+    Ok(addons
+        .iter()
+        .map(|(source, value)| format!("* ``{value}`` ({})", AddonSource::label(source)))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+// The jar is written next to its final path and only renamed once complete, so that a failed
+// download never gets installed.
+async fn download_jar(url: &str, path: &Path) -> Result<(), ClientError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let part = path.with_extension("jar.part");
+    if let Err(error) = write_part(url, &part).await {
+        if let Err(error) = fs::remove_file(&part).await
+            && error.kind() != std::io::ErrorKind::NotFound
+        {
+            log::warn!("Cannot remove {}: {error}", part.display());
+        }
+        return Err(error);
+    }
+
+    fs::rename(part, path).await?;
+    Ok(())
+}
+
+async fn write_part(url: &str, part: &Path) -> Result<(), ClientError> {
+    let mut response = reqwest::get(url).await?.error_for_status()?;
+    let mut file = fs::File::create(part).await?;
+    let mut written: u64 = 0;
+    while let Some(chunk) = response.chunk().await? {
+        written += chunk.len() as u64;
+        if written > u64::from(MAX_JAR_SIZE) {
+            return Err(too_large());
+        }
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    Ok(())
+}
+
+fn too_large() -> ClientError {
+    ClientError::Other(format!(
+        "Le fichier dépasse {} Mo.",
+        MAX_JAR_SIZE / 1024 / 1024
+    ))
+}
+
+fn file_name(file: &Attachment) -> Result<&str, ClientError> {
+    let path = Path::new(&file.filename);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| !name.starts_with('.'))
+        .filter(|_| {
+            path.extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("jar"))
+        })
+        .ok_or(ClientError::OtherStatic("Le fichier doit être un .jar."))?;
+
+    // The name ends up in the comma-separated glob list of jars kept across starts.
+    if name.contains([',', '*', '?', '[', ']']) {
+        return Err(ClientError::OtherStatic(
+            "Le nom du fichier ne doit pas contenir de virgule ni de `*`, `?`, `[` ou `]`.",
+        ));
+    }
+
+    if file.size > MAX_JAR_SIZE {
+        return Err(too_large());
+    }
+
+    Ok(name)
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+pub fn register(kind: AddonKind) -> CreateCommand {
+    let (command, description_en, description_fr) = match kind {
+        AddonKind::Mod => (
+            "mods",
+            "Manage the mods of a server.",
+            "Gère les mods d'un serveur.",
+        ),
+        AddonKind::Plugin => (
+            "plugins",
+            "Manage the plugins of a server.",
+            "Gère les plugins d'un serveur.",
+        ),
+    };
+
+    let name_option = || {
+        CreateCommandOption::new(CommandOptionType::String, "name", "Le nom du serveur.")
+            .description_localized("en-US", "The name of the server.")
+            .description_localized("en-GB", "The name of the server.")
+            .required(true)
+            .max_length(25)
+    };
+
+    CreateCommand::new(command)
+        .description(description_en)
+        .description_localized("en-US", description_en)
+        .description_localized("en-GB", description_en)
+        .description_localized("fr", description_fr)
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                format!("Ajoute un {}.", kind.as_str()),
+            )
+            .description_localized("en-US", format!("Add a {}.", kind.as_str()))
+            .description_localized("en-GB", format!("Add a {}.", kind.as_str()))
+            .add_sub_option(name_option())
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "project",
+                    "Le projet Modrinth ou l'URL https du jar.",
+                )
+                .description_localized("en-US", "The Modrinth project or https URL of the jar.")
+                .description_localized("en-GB", "The Modrinth project or https URL of the jar."),
+            )
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Attachment,
+                    "file",
+                    "Le fichier .jar à installer.",
+                )
+                .description_localized("en-US", "The .jar file to install.")
+                .description_localized("en-GB", "The .jar file to install."),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "remove",
+                format!("Retire un {}.", kind.as_str()),
+            )
+            .description_localized("en-US", format!("Remove a {}.", kind.as_str()))
+            .description_localized("en-GB", format!("Remove a {}.", kind.as_str()))
+            .add_sub_option(name_option())
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "project",
+                    "Le projet, l'URL ou le fichier à retirer.",
+                )
+                .description_localized("en-US", "The project, URL or file to remove.")
+                .description_localized("en-GB", "The project, URL or file to remove.")
+                .required(true),
+            ),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                format!("Liste les {}s.", kind.as_str()),
+            )
+            .description_localized("en-US", format!("List the {}s.", kind.as_str()))
+            .description_localized("en-GB", format!("List the {}s.", kind.as_str()))
+            .add_sub_option(name_option()),
+        )
+}
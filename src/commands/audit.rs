@@ -0,0 +1,198 @@
+use crate::client::error::ClientError;
+use crate::commands::{extract_str_optional, extract_user_optional};
+use crate::database::models::audit_events::AuditEvents;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::audit_events;
+use crate::database::schemas::audit_events::dsl as audit_dsl;
+use crate::server::audit::AuditEntry;
+use crate::server::permission::is_admin;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx};
+use diesel::pg::Pg;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateActionRow, CreateButton, CreateCommand,
+    CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponseMessage,
+    EditInteractionResponse, Permissions, UserId,
+};
+
+const EVENTS_PER_PAGE: u64 = 10;
+
+// Filters of a page, carried in the custom id of its buttons as `audit-<page>-<user>-<server>`.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub server: Option<String>,
+    pub user: Option<UserId>,
+}
+
+impl AuditFilter {
+    fn custom_id(&self, page: u64) -> String {
+        format!(
+            "audit-{page}-{}-{}",
+            self.user.map_or(0, UserId::get),
+            self.server.as_deref().unwrap_or_default()
+        )
+    }
+
+    #[must_use]
+    pub fn from_custom_id(custom_id: &str) -> (u64, Self) {
+        let mut parts = custom_id.splitn(4, '-').skip(1);
+        let page = parts
+            .next()
+            .and_then(|page| page.parse::<u64>().ok())
+            .unwrap_or_default();
+        let user = parts
+            .next()
+            .and_then(|user| user.parse::<u64>().ok())
+            .filter(|user| *user != 0)
+            .map(UserId::new);
+        let server = parts
+            .next()
+            .filter(|server| !server.is_empty())
+            .map(str::to_owned);
+
+        (page, Self { server, user })
+    }
+
+    fn query(&self) -> Result<audit_events::BoxedQuery<'_, Pg>, ClientError> {
+        let mut query = audit_dsl::audit_events.into_boxed();
+        if let Some(server) = &self.server {
+            query = query.filter(audit_dsl::server_name.eq(server));
+        }
+        if let Some(user) = self.user {
+            query = query.filter(audit_dsl::user_id.eq(i64::try_from(user.get())?));
+        }
+        Ok(query)
+    }
+}
+
+async fn get_events(
+    ctx: &Context,
+    filter: &AuditFilter,
+    page: &mut u64,
+) -> Result<(Vec<AuditEvents>, u64), ClientError> {
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let events_count: i64 = filter.query()?.count().get_result(&mut conn).await?;
+    let pages_count = events_count
+        .cast_unsigned()
+        .div_ceil(EVENTS_PER_PAGE)
+        .max(1);
+    *page = (*page).clamp(1, pages_count);
+
+    let events: Vec<AuditEvents> = filter
+        .query()?
+        .select(AuditEvents::as_select())
+        .order_by(audit_dsl::created_at.desc())
+        .limit(EVENTS_PER_PAGE.cast_signed())
+        .offset(((*page - 1) * EVENTS_PER_PAGE).cast_signed())
+        .load(&mut conn)
+        .await?;
+
+    Ok((events, pages_count))
+}
+
+async fn page(
+    ctx: &Context,
+    filter: &AuditFilter,
+    mut page: u64,
+) -> Result<(CreateEmbed, Vec<CreateActionRow>), ClientError> {
+    let (events, pages_count) = get_events(ctx, filter, &mut page).await?;
+    if events.is_empty() {
+        let embed = CreateEmbed::new()
+            .description("Aucune action enregistrée.")
+            .color(EMBED_COLOR);
+        return Ok((embed, Vec::new()));
+    }
+
+    let lines: Vec<String> = events
+        .iter()
+        .map(|event| {
+            format!(
+                "* <t:{}:f> {}",
+                event.created_at.timestamp(),
+                AuditEntry::from(event).describe(event.user_id, event.error.as_deref())
+            )
+        })
+        .collect();
+
+    let embed = CreateEmbed::new()
+        .title("Journal des actions")
+        .description(lines.join("\n"))
+        .footer(CreateEmbedFooter::new(format!("Page {page}/{pages_count}")))
+        .color(EMBED_COLOR);
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(filter.custom_id(page - 1))
+            .label("Précédent")
+            .disabled(page == 1),
+        CreateButton::new(filter.custom_id(page + 1))
+            .label("Suivant")
+            .disabled(page == pages_count),
+    ]);
+
+    Ok((embed, vec![buttons]))
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    if !is_admin(command.member.as_deref())? {
+        return Err(ClientError::OtherStatic(
+            "Seuls les administrateurs peuvent consulter le journal.",
+        ));
+    }
+
+    let options = command.data.options();
+    let filter = AuditFilter {
+        server: extract_str_optional("server", &options)?.map(str::to_lowercase),
+        user: extract_user_optional("user", &options)?.map(|user| user.id),
+    };
+
+    let (embed, components) = page(ctx, &filter, 1).await?;
+
+    // Ephemeral, so that only the administrator who asked can turn the pages.
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .ephemeral(true)
+                    .add_embed(embed)
+                    .components(components),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_page(
+    ctx: &Context,
+    page_number: u64,
+    filter: &AuditFilter,
+) -> Result<EditInteractionResponse, ClientError> {
+    let (embed, components) = page(ctx, filter, page_number).await?;
+
+    Ok(EditInteractionResponse::new()
+        .embed(embed)
+        .components(components))
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("audit")
+        .description("Show the log of the actions on the servers.")
+        .description_localized("en-US", "Show the log of the actions on the servers.")
+        .description_localized("en-GB", "Show the log of the actions on the servers.")
+        .description_localized("fr", "Affiche le journal des actions sur les serveurs.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "server", "Le nom du serveur.")
+                .description_localized("en-US", "The name of the server.")
+                .description_localized("en-GB", "The name of the server.")
+                .max_length(25),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::User, "user", "L'utilisateur.")
+                .description_localized("en-US", "The user.")
+                .description_localized("en-GB", "The user."),
+        )
+}
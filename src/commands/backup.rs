@@ -0,0 +1,293 @@
+use crate::client::error::ClientError;
+use crate::commands::{extract_integer, extract_str, extract_subcommand};
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::interarction::confirm::{Action, ask, loss_summary};
+use crate::server::backup::{BackupKind, create_backup, list_backups, restore_backup};
+use crate::server::lifecycle::{ServerStatus, check_container_stopped};
+use crate::server::lock::lock_server;
+use crate::server::permission::{MemberRole, require};
+use crate::util::{EMBED_COLOR, format_size, get_pool_from_ctx, get_runtime_from_ctx};
+use chrono::Local;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, EditInteractionResponse,
+};
+
+const LISTED_BACKUPS: usize = 20;
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let options = command.data.options();
+    let (subcommand, options) = extract_subcommand(&options)?;
+    let name = extract_str("name", &options)?.to_lowercase();
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let (id, status): (i64, ServerStatus) = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::status))
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+
+    match subcommand {
+        "list" => list(ctx, command, &mut conn, id, &name).await,
+        "now" => now(ctx, command, &mut conn, id, &name).await,
+        "restore" => {
+            let backup_id = extract_integer("backup", &options)?;
+            restore(ctx, command, &mut conn, id, status, &name, backup_id).await
+        }
+        _ => Err(ClientError::OtherStatic("Sous-commande inconnue.")),
+    }
+}
+
+async fn list(
+    ctx: &Context,
+    command: &CommandInteraction,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    name: &str,
+) -> Result<(), ClientError> {
+    let backups = list_backups(conn, id).await?;
+
+    let description = if backups.is_empty() {
+        "Aucune sauvegarde.".to_owned()
+    } else {
+        let mut lines: Vec<String> = backups
+            .iter()
+            .take(LISTED_BACKUPS)
+            .map(|backup| {
+                format!(
+                    "* ``#{}`` {} — {} ({})",
+                    backup.id,
+                    backup
+                        .created_at
+                        .with_timezone(&Local)
+                        .format("%d/%m/%Y %H:%M"),
+                    format_size(backup.size.cast_unsigned()),
+                    BackupKind::label(&backup.kind)
+                )
+            })
+            .collect();
+        if backups.len() > LISTED_BACKUPS {
+            lines.push(format!(
+                "*... et {} plus anciennes.*",
+                backups.len() - LISTED_BACKUPS
+            ));
+        }
+        lines.join("\n")
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!("Sauvegardes de ``{name}``"))
+        .description(description)
+        .color(EMBED_COLOR);
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().add_embed(embed),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn now(
+    ctx: &Context,
+    command: &CommandInteraction,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    name: &str,
+) -> Result<(), ClientError> {
+    require(
+        conn,
+        id,
+        command.user.id,
+        command.member.as_deref(),
+        MemberRole::Operator,
+    )
+    .await?;
+
+    let _guard = lock_server(ctx, id).await?;
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new(),
+            ),
+        )
+        .await?;
+
+    let backup = create_backup(
+        conn,
+        get_runtime_from_ctx(ctx).await?.as_ref(),
+        id,
+        BackupKind::Manual,
+    )
+    .await?;
+
+    let embed = CreateEmbed::new()
+        .description(format!(
+            "**Sauvegarde ``#{}`` de ``{name}`` créée ({}) !**",
+            backup.id,
+            format_size(backup.size.cast_unsigned())
+        ))
+        .color(EMBED_COLOR);
+
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().add_embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+async fn restore(
+    ctx: &Context,
+    command: &CommandInteraction,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    status: ServerStatus,
+    name: &str,
+    backup_id: i64,
+) -> Result<(), ClientError> {
+    check_stopped(status)?;
+    require(
+        conn,
+        id,
+        command.user.id,
+        command.member.as_deref(),
+        MemberRole::Operator,
+    )
+    .await?;
+
+    let summary = loss_summary(conn, id).await?;
+    ask(
+        ctx,
+        command,
+        Action::Restore(id, backup_id),
+        format!("Restaurer ``{name}`` depuis la sauvegarde ``#{backup_id}`` ?"),
+        format!("Le monde actuel sera remplacé :\n{summary}"),
+    )
+    .await
+}
+
+// Runs once the restoration has been confirmed, the status may have changed since.
+pub async fn restore_server(
+    ctx: &Context,
+    id: i64,
+    backup_id: i64,
+    user_id: u64,
+) -> Result<String, ClientError> {
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let _guard = lock_server(ctx, id).await?;
+
+    let (name, status): (String, ServerStatus) = servers_dsl::servers
+        .select((servers_dsl::name, servers_dsl::status))
+        .filter(servers_dsl::id.eq(id))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+    check_stopped(status)?;
+    let runtime = get_runtime_from_ctx(ctx).await?;
+    check_container_stopped(runtime.as_ref(), id).await?;
+
+    let safety = restore_backup(
+        &mut conn,
+        runtime.as_ref(),
+        id,
+        backup_id,
+        i64::try_from(user_id)?,
+    )
+    .await?;
+
+    let safety = safety.map_or_else(String::new, |safety| {
+        format!(
+            "\nL'ancien monde a été gardé dans la sauvegarde ``#{}``.",
+            safety.id
+        )
+    });
+
+    Ok(format!(
+        "**``{name}`` a été restauré depuis la sauvegarde ``#{backup_id}`` !**{safety}"
+    ))
+}
+
+const fn check_stopped(status: ServerStatus) -> Result<(), ClientError> {
+    if matches!(status, ServerStatus::Stopped | ServerStatus::Crashed) {
+        Ok(())
+    } else {
+        Err(ClientError::OtherStatic(
+            "Le serveur doit être arrêté pour être restauré.",
+        ))
+    }
+}
+
+pub fn register() -> CreateCommand {
+    let name_option = || {
+        CreateCommandOption::new(CommandOptionType::String, "name", "Le nom du serveur.")
+            .description_localized("en-US", "The name of the server.")
+            .description_localized("en-GB", "The name of the server.")
+            .required(true)
+            .max_length(25)
+    };
+
+    CreateCommand::new("backup")
+        .description("Manage the backups of a server.")
+        .description_localized("en-US", "Manage the backups of a server.")
+        .description_localized("en-GB", "Manage the backups of a server.")
+        .description_localized("fr", "Gère les sauvegardes d'un serveur.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "Liste les sauvegardes.",
+            )
+            .description_localized("en-US", "List the backups.")
+            .description_localized("en-GB", "List the backups.")
+            .add_sub_option(name_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "now",
+                "Sauvegarde le serveur maintenant.",
+            )
+            .description_localized("en-US", "Back up the server now.")
+            .description_localized("en-GB", "Back up the server now.")
+            .add_sub_option(name_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "restore",
+                "Restaure le serveur depuis une sauvegarde.",
+            )
+            .description_localized("en-US", "Restore the server from a backup.")
+            .description_localized("en-GB", "Restore the server from a backup.")
+            .add_sub_option(name_option())
+            .add_sub_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "backup",
+                    "La sauvegarde à restaurer.",
+                )
+                .description_localized("en-US", "The backup to restore.")
+                .description_localized("en-GB", "The backup to restore.")
+                .required(true)
+                .set_autocomplete(true),
+            ),
+        )
+}
@@ -0,0 +1,146 @@
+use crate::client::error::ClientError;
+use crate::commands::extract_str;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::console::run_command;
+use crate::server::lifecycle::ServerStatus;
+use crate::server::permission::is_admin;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, get_runtime_from_ctx, truncate};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, EditInteractionResponse, Permissions,
+};
+
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+const MAX_COMMAND_LENGTH: usize = 200;
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    if !is_admin(command.member.as_deref())? {
+        return Err(ClientError::OtherStatic(
+            "Seuls les administrateurs peuvent utiliser la console.",
+        ));
+    }
+
+    let name = extract_str("name", &command.data.options())?.to_lowercase();
+    let server_command = extract_str("command", &command.data.options())?;
+    let server_command = server_command.strip_prefix('/').unwrap_or(server_command);
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let (id, status): (i64, ServerStatus) = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::status))
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+
+    if status != ServerStatus::Running {
+        return Err(ClientError::OtherStatic("Le serveur n'est pas lancé."));
+    }
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new(),
+            ),
+        )
+        .await?;
+
+    let output = run_command(
+        &mut conn,
+        get_runtime_from_ctx(ctx).await?.as_ref(),
+        id,
+        server_command,
+    )
+    .await?;
+
+    log::info!("Console command on {name} : {server_command}");
+
+    let embed = CreateEmbed::new()
+        .title(format!("Console de ``{name}``"))
+        .description(describe(server_command, &output))
+        .color(EMBED_COLOR);
+
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().add_embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+// Backticks would close the code blocks, they are replaced by a look-alike.
+fn describe(server_command: &str, output: &str) -> String {
+    let server_command = truncate(&server_command.replace('`', "ˋ"), MAX_COMMAND_LENGTH);
+    let header = format!("``/{server_command}``\n```\n");
+    let footer = "\n```";
+
+    let output = output.trim();
+    let output = if output.is_empty() {
+        "(aucune sortie)".to_owned()
+    } else {
+        // The lengths are in bytes, at least as many as characters, and truncate adds a character.
+        let max_length = MAX_DESCRIPTION_LENGTH - header.len() - footer.len() - 1;
+        truncate(&output.replace('`', "ˋ"), max_length)
+    };
+
+    format!("{header}{output}{footer}")
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("console")
+        .description("Run a command in a server console.")
+        .description_localized("en-US", "Run a command in a server console.")
+        .description_localized("en-GB", "Run a command in a server console.")
+        .description_localized("fr", "Exécute une commande dans la console d'un serveur.")
+        .default_member_permissions(Permissions::ADMINISTRATOR)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "name", "Le nom du serveur.")
+                .description_localized("en-US", "The name of the server.")
+                .description_localized("en-GB", "The name of the server.")
+                .required(true)
+                .max_length(25),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "command",
+                "La commande à exécuter.",
+            )
+            .description_localized("en-US", "The command to run.")
+            .description_localized("en-GB", "The command to run.")
+            .required(true),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptions_fit_in_an_embed() {
+        for (command, output) in [
+            ("list", "There are 0 players online"),
+            ("say ```", "```\n"),
+            (&"a".repeat(5000) as &str, &"é".repeat(5000) as &str),
+            ("data get", &"`".repeat(5000)),
+        ] {
+            let description = describe(command, output);
+            assert!(description.chars().count() <= MAX_DESCRIPTION_LENGTH);
+            assert_eq!(description.matches("```").count(), 2, "{description}");
+        }
+    }
+
+    #[test]
+    fn empty_output() {
+        assert_eq!(
+            describe("save-all", "  \n"),
+            "``/save-all``\n```\n(aucune sortie)\n```"
+        );
+    }
+}
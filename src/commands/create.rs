@@ -1,156 +1,44 @@
-use std::path::Path;
-
 use crate::client::error::ClientError;
 use crate::commands::extract_str;
 use crate::commands::extract_str_optional;
 use crate::database::postgresql::PgPool;
 use crate::database::postgresql::PgPooled;
-use crate::database::schemas::servers::dsl as servers_dsl;
-use crate::util::parse_key;
-use crate::util::{EMBED_COLOR, get_pool_from_ctx};
-use diesel::dsl::exists;
-use diesel::{ExpressionMethods, QueryDsl, insert_into};
-use diesel_async::RunQueryDsl;
-use serde_yml::Mapping;
-use serde_yml::Value;
+use crate::server::provision::{create_server, default_memory};
+use crate::server::quota::check_create;
+use crate::server::software::ServerType;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, get_runtime_from_ctx};
 use serenity::all::CommandInteraction;
 use serenity::all::CreateInteractionResponseMessage;
 use serenity::all::{CommandOptionType, Context, CreateCommand, CreateCommandOption, CreateEmbed};
-use tokio::fs;
 
 pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
     let name = extract_str("name", &command.data.options())?.to_lowercase();
     let ver = extract_str_optional("version", &command.data.options())?;
     let difficulty_option = extract_str_optional("difficulty", &command.data.options())?;
+    let server_type = extract_str_optional("type", &command.data.options())?
+        .map_or(Ok(ServerType::Vanilla), str::parse::<ServerType>)
+        .map_err(ClientError::Other)?;
 
     let pool: PgPool = get_pool_from_ctx(ctx).await?;
     let mut conn: PgPooled = pool.get().await?;
 
-    let serv_exist: bool = diesel::select(exists(
-        servers_dsl::servers.filter(servers_dsl::name.eq(&name)),
-    ))
-    .get_result(&mut conn)
+    check_create(
+        &mut conn,
+        get_runtime_from_ctx(ctx).await?.as_ref(),
+        command.user.id,
+        default_memory()?,
+    )
     .await?;
-    if serv_exist {
-        return Err(ClientError::OtherStatic("Ce nom de serveur existe déjà."));
-    }
-
-    let ports_used: Vec<i64> = servers_dsl::servers
-        .select(servers_dsl::port)
-        .order_by(servers_dsl::port)
-        .load::<i64>(&mut conn)
-        .await?;
-
-    let mut port = 0;
-
-    for i in parse_key::<i64>("MIN_PORT")?..parse_key::<i64>("MAX_PORT")? {
-        if !ports_used.contains(&i) {
-            port = i;
-            break;
-        }
-    }
-
-    if port == 0 {
-        return Err(ClientError::OtherStatic("Pas de port disponible."));
-    }
-
-    let mut services = Mapping::new();
-
-    let mut mc = Mapping::new();
-    mc.insert(
-        Value::String("image".into()),
-        Value::String("itzg/minecraft-server".into()),
-    );
-    mc.insert(Value::String("tty".into()), Value::Bool(true));
-    mc.insert(Value::String("stdin_open".into()), Value::Bool(true));
-    mc.insert(
-        Value::String("ports".into()),
-        Value::Sequence(vec![Value::String(format!("{}:25565", port.to_string(),))]),
-    );
-
-    let mut env = Mapping::new();
-
-    env.insert(Value::String("EULA".into()), Value::String("TRUE".into()));
-
-    env.insert(
-        Value::String("OPS".into()),
-        Value::String(parse_key::<String>("ADMIN_PLAYER")?),
-    );
-
-    if let Some(version) = ver {
-        let json = fs::read_to_string("versions.json").await?;
-        let versions: Vec<String> = serde_json::from_str(&json)?;
-
-        if versions.iter().any(|e| e == version) {
-            env.insert(
-                Value::String("VERSION".into()),
-                Value::String(version.into()),
-            );
-        } else {
-            return Err(ClientError::Other(format!(
-                "{version} n'est pas une version valide."
-            )));
-        }
-    }
-
-    if let Some(difficulty) = difficulty_option {
-        env.insert(
-            Value::String("DIFFICULTY".into()),
-            Value::String(difficulty.into()),
-        );
-    }
-
-    env.insert(
-        Value::String("MAX_MEMORY".into()),
-        Value::String(parse_key::<String>("MAX_MEMORY")?),
-    );
-
-    mc.insert(Value::String("environment".into()), Value::Mapping(env));
-
-    mc.insert(
-        Value::String("volumes".into()),
-        Value::Sequence(vec![Value::String("./data:/data".into())]),
-    );
-
-    let mut healthcheck = Mapping::new();
-
-    healthcheck.insert(
-        Value::String("test".into()),
-        Value::String("mc-health".into()),
-    );
-    healthcheck.insert(
-        Value::String("start_period".into()),
-        Value::String("1m".into()),
-    );
-    healthcheck.insert(Value::String("interval".into()), Value::String("5s".into()));
-    healthcheck.insert(Value::String("retries".into()), Value::String("20".into()));
-
-    mc.insert("healthcheck".into(), Value::Mapping(healthcheck));
 
-    services.insert(Value::String("mc".into()), Value::Mapping(mc));
-
-    let mut root = Mapping::new();
-    root.insert(Value::String("services".into()), Value::Mapping(services));
-
-    let yml_str = serde_yml::to_string(&root)?;
-
-    let id: i64 = insert_into(servers_dsl::servers)
-        .values((
-            servers_dsl::name.eq(&name),
-            servers_dsl::version.eq(ver.map_or_else(|| "latest", |version| version).to_string()),
-            servers_dsl::difficulty
-                .eq(difficulty_option.map_or_else(|| "easy", |difficulty| difficulty)),
-            servers_dsl::port.eq(port),
-            servers_dsl::started.eq(false),
-        ))
-        .returning(servers_dsl::id)
-        .get_result(&mut conn)
-        .await?;
-
-    let dir = Path::new("worlds").join(id.to_string());
-
-    fs::create_dir_all(&dir).await?;
-    fs::write(dir.join("docker-compose.yml"), yml_str).await?;
+    create_server(
+        &mut conn,
+        &name,
+        ver,
+        difficulty_option,
+        server_type,
+        command.user.id,
+    )
+    .await?;
 
     log::info!("Created \"{name}\" server!");
 
@@ -171,6 +59,13 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), Clie
 }
 
 pub fn register() -> CreateCommand {
+    let type_option = ServerType::ALL.into_iter().fold(
+        CreateCommandOption::new(CommandOptionType::String, "type", "Le type du serveur.")
+            .description_localized("en-US", "The software of the server to be created.")
+            .description_localized("en-GB", "The software of the server to be created."),
+        |option, server_type| option.add_string_choice(server_type.label(), server_type.as_str()),
+    );
+
     CreateCommand::new("create")
         .description("Create a server.")
         .description_localized("en-US", "Create a server.")
@@ -210,4 +105,5 @@ pub fn register() -> CreateCommand {
             .add_string_choice("normal", "normal")
             .add_string_choice("hard", "hard"),
         )
+        .add_option(type_option)
 }
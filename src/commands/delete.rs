@@ -1,18 +1,19 @@
-use std::path::Path;
-
 use crate::client::error::ClientError;
 use crate::commands::extract_str;
 use crate::database::postgresql::{PgPool, PgPooled};
 use crate::database::schemas::servers::dsl as servers_dsl;
-use crate::util::{EMBED_COLOR, get_pool_from_ctx};
-use diesel::dsl::exists;
-use diesel::{ExpressionMethods, QueryDsl, delete};
+use crate::interarction::confirm::{Action, ask, loss_summary};
+use crate::server::lifecycle::{ServerStatus, transition};
+use crate::server::lock::lock_server;
+use crate::server::permission::{MemberRole, require};
+use crate::server::trash::{purge_date, trash_server};
+use crate::util::{get_pool_from_ctx, get_runtime_from_ctx};
+use chrono::Local;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
 use diesel_async::RunQueryDsl;
 use serenity::all::{
     CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    CreateEmbed, CreateInteractionResponseMessage,
 };
-use tokio::fs;
 
 pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
     let name = extract_str("name", &command.data.options())?.to_lowercase();
@@ -20,57 +21,83 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), Clie
     let pool: PgPool = get_pool_from_ctx(ctx).await?;
     let mut conn: PgPooled = pool.get().await?;
 
-    let serv_exist: bool = diesel::select(exists(
-        servers_dsl::servers.filter(servers_dsl::name.eq(&name)),
-    ))
-    .get_result(&mut conn)
+    let (id, status): (i64, ServerStatus) = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::status))
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+
+    require(
+        &mut conn,
+        id,
+        command.user.id,
+        command.member.as_deref(),
+        MemberRole::Operator,
+    )
     .await?;
 
-    if !serv_exist {
-        return Err(ClientError::OtherStatic("Ce serveur n'existe pas."));
+    if !status.can_become(ServerStatus::Deleting) {
+        return Err(ClientError::OtherStatic(status.refusal()));
     }
 
-    let serv_started: bool = diesel::select(exists(
-        servers_dsl::servers
-            .filter(servers_dsl::started.eq(true))
-            .filter(servers_dsl::name.eq(&name)),
-    ))
-    .get_result(&mut conn)
-    .await?;
+    let summary = loss_summary(&mut conn, id).await?;
+    ask(
+        ctx,
+        command,
+        Action::Delete(id),
+        format!("Supprimer ``{name}`` ?"),
+        summary,
+    )
+    .await
+}
 
-    if serv_started {
-        return Err(ClientError::OtherStatic("Le serveur est lancé."));
-    }
+// Runs once the deletion has been confirmed, the status may have changed since.
+pub async fn delete_server(ctx: &Context, id: i64) -> Result<String, ClientError> {
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
 
-    let id: i64 = servers_dsl::servers
-        .select(servers_dsl::id)
-        .filter(servers_dsl::name.eq(&name))
+    let _guard = lock_server(ctx, id).await?;
+
+    let name: String = servers_dsl::servers
+        .select(servers_dsl::name)
+        .filter(servers_dsl::id.eq(id))
+        .filter(servers_dsl::deleted_at.is_null())
         .get_result(&mut conn)
-        .await?;
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
 
-    fs::remove_dir_all(Path::new("worlds").join(id.to_string())).await?;
+    transition(&mut conn, id, ServerStatus::Deleting).await?;
 
-    delete(servers_dsl::servers)
-        .filter(servers_dsl::name.eq(&name))
-        .execute(&mut pool.get().await?)
-        .await?;
+    let removed = async {
+        get_runtime_from_ctx(ctx).await?.down(id).await?;
+        trash_server(&mut conn, id).await
+    }
+    .await;
+    let deleted_at = match removed {
+        Ok(deleted_at) => deleted_at,
+        Err(error) => {
+            transition(&mut conn, id, ServerStatus::Stopped).await?;
+            return Err(error);
+        }
+    };
+    transition(&mut conn, id, ServerStatus::Stopped).await?;
 
     log::info!("Deleted server : {name}!");
 
-    let embed = CreateEmbed::new()
-        .description(format!("**Serveur ``{name}`` supprimé !**"))
-        .color(EMBED_COLOR);
-
-    command
-        .create_response(
-            &ctx.http,
-            serenity::builder::CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new().add_embed(embed),
-            ),
+    let purge = purge_date(deleted_at).map_or_else(String::new, |date| {
+        format!(
+            "\nIl pourra être restauré avec ``/undelete`` jusqu'au {}.",
+            date.with_timezone(&Local).format("%d/%m/%Y %H:%M")
         )
-        .await?;
+    });
 
-    Ok(())
+    Ok(format!(
+        "**Serveur ``{name}`` placé dans la corbeille !**{purge}"
+    ))
 }
 
 pub fn register() -> CreateCommand {
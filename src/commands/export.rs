@@ -0,0 +1,122 @@
+use crate::client::error::ClientError;
+use crate::commands::extract_str;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::lock::lock_server;
+use crate::server::permission::{MemberRole, require};
+use crate::server::snapshot::snapshot;
+use crate::util::{EMBED_COLOR, format_size, get_pool_from_ctx, get_runtime_from_ctx};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommand,
+    CreateCommandOption, CreateEmbed, CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use std::path::PathBuf;
+use tokio::fs;
+
+const UPLOAD_LIMIT: u64 = 10 * 1024 * 1024;
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let name = extract_str("name", &command.data.options())?.to_lowercase();
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let id: i64 = servers_dsl::servers
+        .select(servers_dsl::id)
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+
+    require(
+        &mut conn,
+        id,
+        command.user.id,
+        command.member.as_deref(),
+        MemberRole::Operator,
+    )
+    .await?;
+
+    let _guard = lock_server(ctx, id).await?;
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new(),
+            ),
+        )
+        .await?;
+
+    let export_dir =
+        PathBuf::from(std::env::var("EXPORT_DIR").unwrap_or_else(|_| "exports".to_owned()));
+    // Servers created before names were validated may hold anything, so only the id is trusted.
+    let dest = export_dir.join(format!(
+        "{id}-{}-{}.tar.gz",
+        name.replace(
+            |c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+            "_"
+        ),
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+
+    let size = snapshot(
+        &mut conn,
+        get_runtime_from_ctx(ctx).await?.as_ref(),
+        id,
+        dest.clone(),
+    )
+    .await?;
+
+    log::info!("Exported {name} to {} ({size} bytes)", dest.display());
+
+    let response = if size <= UPLOAD_LIMIT {
+        let attachment = CreateAttachment::path(&dest).await?;
+        fs::remove_file(&dest).await?;
+
+        EditInteractionResponse::new()
+            .add_embed(
+                CreateEmbed::new()
+                    .description(format!("**Monde de ``{name}`` exporté !**"))
+                    .color(EMBED_COLOR),
+            )
+            .new_attachment(attachment)
+    } else {
+        EditInteractionResponse::new().add_embed(
+            CreateEmbed::new()
+                .description(format!(
+                    "**Monde de ``{name}`` exporté !**\nL'archive ({}) est trop lourde pour Discord, elle a été enregistrée dans ``{}``.",
+                    format_size(size),
+                    dest.display()
+                ))
+                .color(EMBED_COLOR),
+        )
+    };
+
+    command.edit_response(&ctx.http, response).await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("export")
+        .description("Export the world of a server.")
+        .description_localized("en-US", "Export the world of a server.")
+        .description_localized("en-GB", "Export the world of a server.")
+        .description_localized("fr", "Exporte le monde d'un serveur.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "name",
+                "Le nom du serveur à exporter.",
+            )
+            .description_localized("en-US", "The name of the server to export.")
+            .description_localized("en-GB", "The name of the server to export.")
+            .required(true)
+            .max_length(25),
+        )
+}
@@ -0,0 +1,198 @@
+use crate::client::error::ClientError;
+use crate::commands::{extract_attachment, extract_str, extract_str_optional};
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::server::archive::{ArchiveFormat, WorldArchive};
+use crate::server::provision::{check_name, create_server, default_memory, discard_server};
+use crate::server::quota::check_create;
+use crate::server::software::ServerType;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, get_runtime_from_ctx, world_dir};
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+const MAX_ARCHIVE_SIZE: u32 = 500 * 1024 * 1024;
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let options = command.data.options();
+    let name = extract_str("name", &options)?.to_lowercase();
+    let file = extract_attachment("file", &options)?;
+    let version = extract_str_optional("version", &options)?;
+    let server_type = extract_str_optional("type", &options)?
+        .map_or(Ok(ServerType::Vanilla), str::parse::<ServerType>)
+        .map_err(ClientError::Other)?;
+
+    let format = ArchiveFormat::from_file_name(&file.filename).ok_or(ClientError::OtherStatic(
+        "L'archive doit être un .zip ou un .tar.gz.",
+    ))?;
+    if file.size > MAX_ARCHIVE_SIZE {
+        return Err(too_large());
+    }
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+
+    // Refuse before the download, and again once it is done as it can take a while.
+    {
+        let mut conn: PgPooled = pool.get().await?;
+        check_create(
+            &mut conn,
+            get_runtime_from_ctx(ctx).await?.as_ref(),
+            command.user.id,
+            default_memory()?,
+        )
+        .await?;
+        check_name(&mut conn, &name).await?;
+    }
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new(),
+            ),
+        )
+        .await?;
+
+    let download = Download(Path::new("worlds").join(format!(".import-{}", command.id)));
+    download_to(&file.url, &download.0).await?;
+
+    let path = download.0.clone();
+    let archive = tokio::task::spawn_blocking(move || WorldArchive::inspect(format, path))
+        .await
+        .map_err(|error| ClientError::Other(error.to_string()))??;
+
+    let mut conn: PgPooled = pool.get().await?;
+    check_create(
+        &mut conn,
+        get_runtime_from_ctx(ctx).await?.as_ref(),
+        command.user.id,
+        default_memory()?,
+    )
+    .await?;
+
+    let id = create_server(
+        &mut conn,
+        &name,
+        version,
+        None,
+        server_type,
+        command.user.id,
+    )
+    .await?;
+
+    let dest = world_dir(id).join("data").join("world");
+    let extracted = tokio::task::spawn_blocking(move || archive.extract(&dest))
+        .await
+        .map_err(|error| ClientError::Other(error.to_string()))
+        .and_then(|result| result);
+    drop(download);
+    if let Err(error) = extracted {
+        log::error!("Cannot import the world of {name}: {error}");
+        discard_server(&mut conn, id).await?;
+        return Err(error);
+    }
+
+    log::info!("Imported \"{name}\" server from {}!", file.filename);
+
+    let embed = CreateEmbed::new()
+        .description(format!(
+            "**Le monde a été importé dans le serveur ``{name}`` !**"
+        ))
+        .color(EMBED_COLOR);
+
+    command
+        .edit_response(&ctx.http, EditInteractionResponse::new().add_embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+// Uploaded archive, removed once imported or on failure.
+struct Download(PathBuf);
+
+impl Drop for Download {
+    fn drop(&mut self) {
+        if let Err(error) = std::fs::remove_file(&self.0)
+            && error.kind() != std::io::ErrorKind::NotFound
+        {
+            log::warn!("Cannot remove {}: {error}", self.0.display());
+        }
+    }
+}
+
+// The archive goes through the disk: worlds are far bigger than the memory given to the bot.
+async fn download_to(url: &str, dest: &Path) -> Result<(), ClientError> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let mut response = reqwest::get(url).await?.error_for_status()?;
+    let mut file = fs::File::create(dest).await?;
+    let mut written: u64 = 0;
+    while let Some(chunk) = response.chunk().await? {
+        written += chunk.len() as u64;
+        if written > u64::from(MAX_ARCHIVE_SIZE) {
+            return Err(too_large());
+        }
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    Ok(())
+}
+
+fn too_large() -> ClientError {
+    ClientError::Other(format!(
+        "L'archive dépasse {} Mo.",
+        MAX_ARCHIVE_SIZE / 1024 / 1024
+    ))
+}
+
+pub fn register() -> CreateCommand {
+    let type_option = ServerType::ALL.into_iter().fold(
+        CreateCommandOption::new(CommandOptionType::String, "type", "Le type du serveur.")
+            .description_localized("en-US", "The software of the server to be created.")
+            .description_localized("en-GB", "The software of the server to be created."),
+        |option, server_type| option.add_string_choice(server_type.label(), server_type.as_str()),
+    );
+
+    CreateCommand::new("import")
+        .description("Create a server from an existing world.")
+        .description_localized("en-US", "Create a server from an existing world.")
+        .description_localized("en-GB", "Create a server from an existing world.")
+        .description_localized("fr", "Création d'un serveur à partir d'un monde existant.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "name",
+                "Le nom du serveur à créer.",
+            )
+            .description_localized("en-US", "The name of the server to be created.")
+            .description_localized("en-GB", "The name of the server to be created.")
+            .required(true)
+            .max_length(25),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Attachment,
+                "file",
+                "Le monde en .zip ou .tar.gz.",
+            )
+            .description_localized("en-US", "The world as a .zip or .tar.gz.")
+            .description_localized("en-GB", "The world as a .zip or .tar.gz.")
+            .required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "version",
+                "La version du serveur.",
+            )
+            .description_localized("en-US", "The version of the server to be created.")
+            .description_localized("en-GB", "The version of the server to be created.")
+            .set_autocomplete(true),
+        )
+        .add_option(type_option)
+}
@@ -0,0 +1,74 @@
+use crate::client::error::ClientError;
+use crate::commands::extract_str;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::minecraft_accounts::dsl as accounts_dsl;
+use crate::server::whitelist::is_valid_player;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx};
+use diesel::{ExpressionMethods, insert_into};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage,
+};
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let player = extract_str("player", &command.data.options())?;
+    if !is_valid_player(player) {
+        return Err(ClientError::Other(format!(
+            "``{player}`` n'est pas un pseudo Minecraft valide."
+        )));
+    }
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let user_id = i64::try_from(command.user.id.get())?;
+    insert_into(accounts_dsl::minecraft_accounts)
+        .values((
+            accounts_dsl::user_id.eq(user_id),
+            accounts_dsl::player.eq(player),
+        ))
+        .on_conflict(accounts_dsl::user_id)
+        .do_update()
+        .set(accounts_dsl::player.eq(player))
+        .execute(&mut conn)
+        .await?;
+
+    log::info!("User {user_id} linked the Minecraft account {player}");
+
+    let embed = CreateEmbed::new()
+        .description(format!(
+            "**Compte ``{player}`` lié !**\nIl sera opérateur de vos serveurs à leur prochain démarrage."
+        ))
+        .color(EMBED_COLOR);
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().add_embed(embed),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("link")
+        .description("Link your Minecraft account.")
+        .description_localized("en-US", "Link your Minecraft account.")
+        .description_localized("en-GB", "Link your Minecraft account.")
+        .description_localized("fr", "Lie votre compte Minecraft.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "player",
+                "Votre pseudo Minecraft.",
+            )
+            .description_localized("en-US", "Your Minecraft name.")
+            .description_localized("en-GB", "Your Minecraft name.")
+            .required(true)
+            .max_length(16),
+        )
+}
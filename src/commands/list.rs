@@ -1,16 +1,21 @@
 use crate::client::error::ClientError;
 use crate::database::postgresql::{PgPool, PgPooled};
 use crate::database::schemas::servers::dsl as servers_dsl;
-use crate::util::{EMBED_COLOR, get_pool_from_ctx, parse_key};
-use diesel::{QueryDsl, Queryable};
+use crate::protocol::slp;
+use crate::server::lifecycle::ServerStatus;
+use crate::server::software::ServerType;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, mc_host, parse_key};
+use diesel::{ExpressionMethods, QueryDsl, Queryable};
 use diesel_async::RunQueryDsl;
 use serenity::all::{
     CommandInteraction, Context, CreateActionRow, CreateButton, CreateCommand, CreateEmbedFooter,
     CreateInteractionResponseMessage, EditInteractionResponse,
 };
 use serenity::builder::CreateEmbed;
+use std::time::Duration;
 
 const ELEMENT_PER_PAGE: u64 = 4;
+const PING_TIMEOUT: Duration = Duration::from_millis(1500);
 
 #[derive(Debug, Clone, Queryable)]
 struct ServersList {
@@ -18,7 +23,8 @@ struct ServersList {
     pub version: String,
     pub difficulty: String,
     pub port: i64,
-    pub started: bool,
+    pub status: ServerStatus,
+    pub server_type: ServerType,
 }
 
 async fn get_servers(
@@ -29,6 +35,7 @@ async fn get_servers(
     let mut conn: PgPooled = pool.get().await?;
 
     let servers_count = servers_dsl::servers
+        .filter(servers_dsl::deleted_at.is_null())
         .count()
         .get_result::<i64>(&mut conn)
         .await?;
@@ -44,8 +51,10 @@ async fn get_servers(
             servers_dsl::version,
             servers_dsl::difficulty,
             servers_dsl::port,
-            servers_dsl::started,
+            servers_dsl::status,
+            servers_dsl::server_type,
         ))
+        .filter(servers_dsl::deleted_at.is_null())
         .limit(ELEMENT_PER_PAGE as i64)
         .offset(i64::try_from(page.saturating_sub(1) * ELEMENT_PER_PAGE)?)
         .order_by(servers_dsl::name)
@@ -55,6 +64,55 @@ async fn get_servers(
     Ok((servers, (servers_count as u64).div_ceil(ELEMENT_PER_PAGE)))
 }
 
+async fn format_servers(servers: Vec<ServersList>) -> Result<Vec<String>, ClientError> {
+    let ip = parse_key::<String>("IP")?;
+    let host = mc_host()?;
+
+    // Every running server is pinged at once so the page stays within the interaction deadline.
+    let pings: Vec<_> = servers
+        .iter()
+        .map(|server| {
+            let host = host.clone();
+            let port = u16::try_from(server.port);
+            let running = server.status == ServerStatus::Running;
+            tokio::spawn(async move {
+                match port {
+                    Ok(port) if running => Some(slp::status(&host, port, PING_TIMEOUT).await),
+                    _ => None,
+                }
+            })
+        })
+        .collect();
+
+    let mut servers_strings = Vec::with_capacity(servers.len());
+    for (server, ping) in servers.into_iter().zip(pings) {
+        let live = match ping.await.ok().flatten() {
+            Some(Ok(status)) => format!(
+                "\n  * **Joueurs** : ``{}/{}``\n  * **MOTD** : ``{}``\n  * **Ping** : ``{} ms``",
+                status.players.online,
+                status.players.max,
+                status.motd(),
+                status.latency.as_millis(),
+            ),
+            Some(Err(_)) => "\n  * **Joignable** : ``non``".to_owned(),
+            None => String::new(),
+        };
+
+        servers_strings.push(format!(
+            "* **{}**\n  * **Adresse** : ``{}:{}``\n  * **Type** : ``{}``\n  * **Version** : ``{}``\n  * **Difficulté** : ``{}``\n  * **État** : ``{}``{live}",
+            server.name,
+            ip,
+            server.port,
+            server.server_type.label(),
+            server.version,
+            server.difficulty,
+            server.status.label(),
+        ));
+    }
+
+    Ok(servers_strings)
+}
+
 pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
     let (servers, pages_count) = get_servers(ctx, &mut 0).await?;
     if servers.is_empty() {
@@ -74,16 +132,7 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), Clie
         return Ok(());
     }
 
-    let ip = parse_key::<String>("IP")?;
-
-    let servers_strings: Vec<String> = servers.iter().map(|server| format!(
-            "* **{}**\n  * **Adresse** : ``{}``\n  * **Version** : ``{}``\n  * **Difficulté** : ``{}``\n  * **Démarré** : ``{}``",
-            server.name,
-            format!("{}:{}", ip, server.port),
-            server.version,
-            server.difficulty,
-            if server.started {"oui"} else {"non"},
-        )).collect();
+    let servers_strings = format_servers(servers).await?;
 
     let embed = CreateEmbed::new()
         .title("Liste des serveurs")
@@ -138,16 +187,7 @@ pub async fn get_page(
         return Ok(msg);
     }
 
-    let ip = parse_key::<String>("IP")?;
-
-    let servers_strings: Vec<String> = servers.iter().map(|server| format!(
-            "* **{}**\n  * **Adresse** : ``{}``\n  * **Version** : ``{}``\n  * **Difficulté** : ``{}``\n  * **Démarré** : ``{}``",
-            server.name,
-            format!("{}:{}", ip, server.port),
-            server.version,
-            server.difficulty,
-            if server.started {"oui"} else {"non"},
-        )).collect();
+    let servers_strings = format_servers(servers).await?;
 
     let msg: EditInteractionResponse = EditInteractionResponse::new()
         .embed(
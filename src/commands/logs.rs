@@ -0,0 +1,210 @@
+use crate::client::error::ClientError;
+use crate::commands::{extract_bool_optional, extract_integer_optional, extract_str};
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::runtime::ContainerRuntime;
+use crate::server::console::secrets;
+use crate::server::permission::{MemberRole, require};
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, get_runtime_from_ctx, redact, truncate_start};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    AutoArchiveDuration, ChannelId, CommandInteraction, CommandOptionType, Context, CreateCommand,
+    CreateCommandOption, CreateEmbed, CreateInteractionResponseMessage, CreateMessage,
+    CreateThread, EditInteractionResponse, Http,
+};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+const DEFAULT_LINES: i64 = 50;
+const MAX_LINES: i64 = 500;
+const MAX_EMBED_LENGTH: usize = 4000;
+const MAX_MESSAGE_LENGTH: usize = 1900;
+const FOLLOW_DURATION: Duration = Duration::from_mins(10);
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let name = extract_str("name", &command.data.options())?.to_lowercase();
+    let lines = extract_integer_optional("lines", &command.data.options())?
+        .unwrap_or(DEFAULT_LINES)
+        .clamp(1, MAX_LINES);
+    let follow = extract_bool_optional("follow", &command.data.options())?.unwrap_or(false);
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+    let runtime = get_runtime_from_ctx(ctx).await?;
+
+    let id: i64 = servers_dsl::servers
+        .select(servers_dsl::id)
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+
+    require(
+        &mut conn,
+        id,
+        command.user.id,
+        command.member.as_deref(),
+        MemberRole::Player,
+    )
+    .await?;
+
+    let secrets = secrets(&mut conn, id).await?;
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new(),
+            ),
+        )
+        .await?;
+
+    let logs = redact(
+        runtime.logs(id, u32::try_from(lines)?).await?.trim(),
+        &secrets,
+    );
+    let logs = if logs.is_empty() {
+        "(aucun log)".to_owned()
+    } else {
+        truncate_start(&logs, MAX_EMBED_LENGTH)
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!("Logs de ``{name}``"))
+        .description(format!("```\n{logs}\n```"))
+        .color(EMBED_COLOR);
+
+    let message = command
+        .edit_response(&ctx.http, EditInteractionResponse::new().add_embed(embed))
+        .await?;
+
+    if follow {
+        let thread = message
+            .channel_id
+            .create_thread_from_message(
+                &ctx.http,
+                message.id,
+                CreateThread::new(format!("Logs {name}"))
+                    .auto_archive_duration(AutoArchiveDuration::OneHour),
+            )
+            .await?;
+
+        tokio::spawn(follow_logs(
+            ctx.http.clone(),
+            runtime,
+            thread.id,
+            id,
+            secrets,
+        ));
+    }
+
+    Ok(())
+}
+
+async fn follow_logs(
+    http: Arc<Http>,
+    runtime: Arc<dyn ContainerRuntime>,
+    thread: ChannelId,
+    id: i64,
+    secrets: Vec<String>,
+) {
+    let end = tokio::time::Instant::now() + FOLLOW_DURATION;
+    let mut since = SystemTime::now();
+
+    while tokio::time::Instant::now() < end {
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+
+        let until = SystemTime::now();
+        let logs = match runtime.logs_between(id, since, until).await {
+            Ok(logs) => logs,
+            Err(error) => {
+                log::error!("Unable to follow logs of server {id}: {error}");
+                break;
+            }
+        };
+        since = until;
+
+        for chunk in chunk_lines(&redact(&logs, &secrets)) {
+            if let Err(error) = thread
+                .send_message(
+                    &http,
+                    CreateMessage::new().content(format!("```\n{chunk}\n```")),
+                )
+                .await
+            {
+                log::error!("Unable to send logs of server {id}: {error}");
+                return;
+            }
+        }
+    }
+
+    if let Err(error) = thread
+        .send_message(
+            &http,
+            CreateMessage::new().content("**Fin du suivi des logs.**"),
+        )
+        .await
+    {
+        log::error!("Unable to send logs of server {id}: {error}");
+    }
+}
+
+fn chunk_lines(logs: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+
+    for line in logs.lines().filter(|line| !line.trim().is_empty()) {
+        let line = line.replace("```", "'''");
+        if !chunk.is_empty() && chunk.len() + line.len() + 1 > MAX_MESSAGE_LENGTH {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        if !chunk.is_empty() {
+            chunk.push('\n');
+        }
+        chunk.push_str(&truncate_start(&line, MAX_MESSAGE_LENGTH));
+    }
+
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("logs")
+        .description("Show the logs of a server.")
+        .description_localized("en-US", "Show the logs of a server.")
+        .description_localized("en-GB", "Show the logs of a server.")
+        .description_localized("fr", "Affiche les logs d'un serveur.")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "name", "Le nom du serveur.")
+                .description_localized("en-US", "The name of the server.")
+                .description_localized("en-GB", "The name of the server.")
+                .required(true)
+                .max_length(25),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "lines",
+                "Le nombre de lignes à afficher.",
+            )
+            .description_localized("en-US", "The number of lines to show.")
+            .description_localized("en-GB", "The number of lines to show.")
+            .min_int_value(1)
+            .max_int_value(MAX_LINES as u64),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "follow",
+                "Suivre les nouveaux logs dans un fil pendant 10 minutes.",
+            )
+            .description_localized("en-US", "Follow new logs in a thread for 10 minutes.")
+            .description_localized("en-GB", "Follow new logs in a thread for 10 minutes."),
+        )
+}
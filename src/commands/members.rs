@@ -0,0 +1,276 @@
+use crate::client::error::ClientError;
+use crate::commands::{extract_str, extract_subcommand, extract_user};
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::server_members::dsl as members_dsl;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::permission::{MemberRole, is_admin, member_role, require};
+use crate::util::{EMBED_COLOR, get_pool_from_ctx};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, delete, insert_into, update};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, ResolvedOption,
+};
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let options = command.data.options();
+    let (subcommand, options) = extract_subcommand(&options)?;
+    let name = extract_str("name", &options)?.to_lowercase();
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let id: i64 = servers_dsl::servers
+        .select(servers_dsl::id)
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+
+    if subcommand == "transfer" {
+        if !is_admin(command.member.as_deref())? {
+            return Err(ClientError::OtherStatic(
+                "Seuls les administrateurs peuvent changer le propriétaire.",
+            ));
+        }
+    } else if subcommand != "list" {
+        require(
+            &mut conn,
+            id,
+            command.user.id,
+            command.member.as_deref(),
+            MemberRole::Owner,
+        )
+        .await?;
+    }
+
+    let description = match subcommand {
+        "add" => add(&mut conn, id, &options).await?,
+        "remove" => remove(&mut conn, id, &options).await?,
+        "transfer" => transfer(&mut conn, id, &options).await?,
+        "list" => list(&mut conn, id).await?,
+        _ => return Err(ClientError::OtherStatic("Sous-commande inconnue.")),
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!("Membres de ``{name}``"))
+        .description(description)
+        .color(EMBED_COLOR);
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().add_embed(embed),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn add(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, ClientError> {
+    let user = extract_user("user", options)?;
+    let role = extract_str("role", options)?
+        .parse::<MemberRole>()
+        .map_err(ClientError::Other)?;
+
+    if role == MemberRole::Owner {
+        return Err(ClientError::OtherStatic(
+            "Un serveur ne peut avoir qu'un propriétaire.",
+        ));
+    }
+    if member_role(conn, id, user.id).await? == Some(MemberRole::Owner) {
+        return Err(ClientError::OtherStatic(
+            "Le rôle du propriétaire ne peut pas être changé.",
+        ));
+    }
+
+    let user_id = i64::try_from(user.id.get())?;
+    insert_into(members_dsl::server_members)
+        .values((
+            members_dsl::server_id.eq(id),
+            members_dsl::user_id.eq(user_id),
+            members_dsl::role.eq(role.as_str()),
+        ))
+        .on_conflict((members_dsl::server_id, members_dsl::user_id))
+        .do_update()
+        .set(members_dsl::role.eq(role.as_str()))
+        .execute(conn)
+        .await?;
+
+    log::info!("User {user_id} is now {} of server {id}", role.as_str());
+
+    Ok(format!("**<@{user_id}> est maintenant {}.**", role.label()))
+}
+
+async fn remove(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, ClientError> {
+    let user = extract_user("user", options)?;
+
+    match member_role(conn, id, user.id).await? {
+        None => {
+            return Err(ClientError::OtherStatic(
+                "Cet utilisateur n'est pas membre du serveur.",
+            ));
+        }
+        Some(MemberRole::Owner) => {
+            return Err(ClientError::OtherStatic(
+                "Le propriétaire ne peut pas être retiré.",
+            ));
+        }
+        Some(_) => {}
+    }
+
+    let user_id = i64::try_from(user.id.get())?;
+    delete(
+        members_dsl::server_members
+            .filter(members_dsl::server_id.eq(id))
+            .filter(members_dsl::user_id.eq(user_id)),
+    )
+    .execute(conn)
+    .await?;
+
+    log::info!("User {user_id} removed from server {id}");
+
+    Ok(format!("**<@{user_id}> n'est plus membre du serveur.**"))
+}
+
+// Also gives an owner to the servers created before owners existed. The previous owner stays
+// an operator.
+async fn transfer(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, ClientError> {
+    let user_id = i64::try_from(extract_user("user", options)?.id.get())?;
+
+    update(
+        members_dsl::server_members
+            .filter(members_dsl::server_id.eq(id))
+            .filter(members_dsl::role.eq(MemberRole::Owner.as_str())),
+    )
+    .set(members_dsl::role.eq(MemberRole::Operator.as_str()))
+    .execute(conn)
+    .await?;
+
+    insert_into(members_dsl::server_members)
+        .values((
+            members_dsl::server_id.eq(id),
+            members_dsl::user_id.eq(user_id),
+            members_dsl::role.eq(MemberRole::Owner.as_str()),
+        ))
+        .on_conflict((members_dsl::server_id, members_dsl::user_id))
+        .do_update()
+        .set(members_dsl::role.eq(MemberRole::Owner.as_str()))
+        .execute(conn)
+        .await?;
+
+    update(servers_dsl::servers.filter(servers_dsl::id.eq(id)))
+        .set(servers_dsl::owner_id.eq(user_id))
+        .execute(conn)
+        .await?;
+
+    log::info!("User {user_id} is now owner of server {id}");
+
+    Ok(format!("**<@{user_id}> est maintenant propriétaire.**"))
+}
+
+async fn list(conn: &mut PgPooled<'_>, id: i64) -> Result<String, ClientError> {
+    let members: Vec<(i64, String)> = members_dsl::server_members
+        .select((members_dsl::user_id, members_dsl::role))
+        .filter(members_dsl::server_id.eq(id))
+        .load(conn)
+        .await?;
+
+    let mut members = members
+        .into_iter()
+        .map(|(user_id, role)| Ok((role.parse::<MemberRole>()?, user_id)))
+        .collect::<Result<Vec<(MemberRole, i64)>, String>>()
+        .map_err(ClientError::Other)?;
+    if members.is_empty() {
+        return Ok("Aucun membre.".to_owned());
+    }
+    members.sort_by(|a, b| b.cmp(a));
+
+    Ok(members
+        .iter()
+        .map(|(role, user_id)| format!("* <@{user_id}> ({})", role.label()))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+pub fn register() -> CreateCommand {
+    let name_option = || {
+        CreateCommandOption::new(CommandOptionType::String, "name", "Le nom du serveur.")
+            .description_localized("en-US", "The name of the server.")
+            .description_localized("en-GB", "The name of the server.")
+            .required(true)
+            .max_length(25)
+    };
+    let user_option = || {
+        CreateCommandOption::new(CommandOptionType::User, "user", "L'utilisateur.")
+            .description_localized("en-US", "The user.")
+            .description_localized("en-GB", "The user.")
+            .required(true)
+    };
+
+    let role_option = [MemberRole::Operator, MemberRole::Player].into_iter().fold(
+        CreateCommandOption::new(CommandOptionType::String, "role", "Le rôle du membre.")
+            .description_localized("en-US", "The role of the member.")
+            .description_localized("en-GB", "The role of the member.")
+            .required(true),
+        |option, role| option.add_string_choice(role.label(), role.as_str()),
+    );
+
+    CreateCommand::new("members")
+        .description("Manage the members of a server.")
+        .description_localized("en-US", "Manage the members of a server.")
+        .description_localized("en-GB", "Manage the members of a server.")
+        .description_localized("fr", "Gère les membres d'un serveur.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Ajoute un membre ou change son rôle.",
+            )
+            .description_localized("en-US", "Add a member or change their role.")
+            .description_localized("en-GB", "Add a member or change their role.")
+            .add_sub_option(name_option())
+            .add_sub_option(user_option())
+            .add_sub_option(role_option),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Retire un membre.")
+                .description_localized("en-US", "Remove a member.")
+                .description_localized("en-GB", "Remove a member.")
+                .add_sub_option(name_option())
+                .add_sub_option(user_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "transfer",
+                "Change le propriétaire (administrateurs).",
+            )
+            .description_localized("en-US", "Change the owner (administrators).")
+            .description_localized("en-GB", "Change the owner (administrators).")
+            .add_sub_option(name_option())
+            .add_sub_option(user_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "list", "Liste les membres.")
+                .description_localized("en-US", "List the members.")
+                .description_localized("en-GB", "List the members.")
+                .add_sub_option(name_option()),
+        )
+}
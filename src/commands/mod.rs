@@ -1,11 +1,29 @@
 use crate::client::error::ClientError;
-use serenity::all::{ResolvedOption, ResolvedValue};
+use crate::database::postgresql::PgPooled;
+use crate::server::console::run_command;
+use crate::server::lifecycle::ServerStatus;
+use crate::util::get_runtime_from_ctx;
+use serenity::all::{Attachment, Context, ResolvedOption, ResolvedValue, User};
 
+pub mod addons;
+pub mod audit;
+pub mod backup;
+pub mod console;
 pub mod create;
 pub mod delete;
+pub mod export;
+pub mod import;
+pub mod link;
 pub mod list;
+pub mod logs;
+pub mod members;
+pub mod op;
+pub mod quota;
+pub mod restart;
 pub mod start;
 pub mod stop;
+pub mod undelete;
+pub mod whitelist;
 
 pub fn extract_str<'a>(name: &str, options: &[ResolvedOption<'a>]) -> Result<&'a str, ClientError> {
     options
@@ -32,3 +50,123 @@ pub fn extract_str_optional<'a>(
             _ => Err(ClientError::Other(format!("Invalid value for arg {name}."))),
         })
 }
+
+pub fn extract_bool_optional(
+    name: &str,
+    options: &[ResolvedOption<'_>],
+) -> Result<Option<bool>, ClientError> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .map_or(Ok(None), |option| match &option.value {
+            ResolvedValue::Boolean(value) => Ok(Some(*value)),
+            _ => Err(ClientError::Other(format!("Invalid value for arg {name}."))),
+        })
+}
+
+pub fn extract_integer(name: &str, options: &[ResolvedOption<'_>]) -> Result<i64, ClientError> {
+    extract_integer_optional(name, options)?
+        .ok_or_else(|| ClientError::Other(format!("Missing arg {name}.")))
+}
+
+pub fn extract_integer_optional(
+    name: &str,
+    options: &[ResolvedOption<'_>],
+) -> Result<Option<i64>, ClientError> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .map_or(Ok(None), |option| match &option.value {
+            ResolvedValue::Integer(value) => Ok(Some(*value)),
+            _ => Err(ClientError::Other(format!("Invalid value for arg {name}."))),
+        })
+}
+
+pub fn extract_attachment<'a>(
+    name: &str,
+    options: &[ResolvedOption<'a>],
+) -> Result<&'a Attachment, ClientError> {
+    extract_attachment_optional(name, options)?
+        .ok_or_else(|| ClientError::Other(format!("Missing arg {name}.")))
+}
+
+pub fn extract_attachment_optional<'a>(
+    name: &str,
+    options: &[ResolvedOption<'a>],
+) -> Result<Option<&'a Attachment>, ClientError> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .map_or(Ok(None), |option| match &option.value {
+            ResolvedValue::Attachment(value) => Ok(Some(*value)),
+            _ => Err(ClientError::Other(format!("Invalid value for arg {name}."))),
+        })
+}
+
+pub fn extract_user<'a>(
+    name: &str,
+    options: &[ResolvedOption<'a>],
+) -> Result<&'a User, ClientError> {
+    extract_user_optional(name, options)?
+        .ok_or_else(|| ClientError::Other(format!("Missing arg {name}.")))
+}
+
+pub fn extract_user_optional<'a>(
+    name: &str,
+    options: &[ResolvedOption<'a>],
+) -> Result<Option<&'a User>, ClientError> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .map_or(Ok(None), |option| match &option.value {
+            ResolvedValue::User(user, _) => Ok(Some(*user)),
+            _ => Err(ClientError::Other(format!("Invalid value for arg {name}."))),
+        })
+}
+
+pub fn extract_subcommand<'a>(
+    options: &[ResolvedOption<'a>],
+) -> Result<(&'a str, Vec<ResolvedOption<'a>>), ClientError> {
+    options.first().map_or_else(
+        || Err(ClientError::OtherStatic("Missing subcommand.")),
+        |option| match &option.value {
+            ResolvedValue::SubCommand(options) => Ok((option.name, options.clone())),
+            _ => Err(ClientError::Other(format!(
+                "Invalid subcommand {}.",
+                option.name
+            ))),
+        },
+    )
+}
+
+// Runs console commands mirroring a settings change on a running server, returning the end of
+// the reply that tells whether the change already applies.
+pub async fn apply_live(
+    ctx: &Context,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    status: ServerStatus,
+    commands: &[String],
+) -> String {
+    if status != ServerStatus::Running {
+        return ", le changement sera appliqué au prochain démarrage.".to_owned();
+    }
+
+    let result = async {
+        let runtime = get_runtime_from_ctx(ctx).await?;
+        for command in commands {
+            run_command(conn, runtime.as_ref(), id, command).await?;
+        }
+        Ok::<(), ClientError>(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => " !".to_owned(),
+        Err(error) => {
+            log::error!("Unable to apply a change live on server {id}: {error}");
+            ", le changement sera appliqué au prochain démarrage (la console n'a pas répondu)."
+                .to_owned()
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::client::error::ClientError;
+use crate::commands::{apply_live, extract_str, extract_subcommand};
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::server_ops::dsl as ops_dsl;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::lifecycle::ServerStatus;
+use crate::server::ops::{OpSource, ops};
+use crate::server::permission::{MemberRole, require};
+use crate::server::whitelist::is_valid_player;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, delete, insert_into};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, ResolvedOption,
+};
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let options = command.data.options();
+    let (subcommand, options) = extract_subcommand(&options)?;
+    let name = extract_str("name", &options)?.to_lowercase();
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let (id, status): (i64, ServerStatus) = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::status))
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+
+    if subcommand != "list" {
+        require(
+            &mut conn,
+            id,
+            command.user.id,
+            command.member.as_deref(),
+            MemberRole::Owner,
+        )
+        .await?;
+    }
+
+    let description = match subcommand {
+        "add" => add(ctx, &mut conn, id, status, &options).await?,
+        "remove" => remove(ctx, &mut conn, id, status, &options).await?,
+        "list" => list(&mut conn, id).await?,
+        _ => return Err(ClientError::OtherStatic("Sous-commande inconnue.")),
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!("Opérateurs de ``{name}``"))
+        .description(description)
+        .color(EMBED_COLOR);
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().add_embed(embed),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn add(
+    ctx: &Context,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    status: ServerStatus,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, ClientError> {
+    let player = extract_str("player", options)?;
+    if !is_valid_player(player) {
+        return Err(ClientError::Other(format!(
+            "``{player}`` n'est pas un pseudo Minecraft valide."
+        )));
+    }
+
+    let ops = ops(conn, id).await?;
+    if ops
+        .iter()
+        .any(|(known, _)| known.eq_ignore_ascii_case(player))
+    {
+        return Err(ClientError::Other(format!(
+            "``{player}`` est déjà opérateur de ce serveur."
+        )));
+    }
+
+    insert_into(ops_dsl::server_ops)
+        .values((ops_dsl::server_id.eq(id), ops_dsl::player.eq(player)))
+        .execute(conn)
+        .await?;
+
+    log::info!("Made {player} op on server {id}");
+
+    let live = apply_live(ctx, conn, id, status, &[format!("op {player}")]).await;
+
+    Ok(format!("**``{player}`` est maintenant opérateur{live}**"))
+}
+
+async fn remove(
+    ctx: &Context,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    status: ServerStatus,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, ClientError> {
+    let player = extract_str("player", options)?;
+
+    let ops = ops(conn, id).await?;
+    let matching: Vec<&(String, OpSource)> = ops
+        .iter()
+        .filter(|(known, _)| known.eq_ignore_ascii_case(player))
+        .collect();
+    let (player, source) = matching
+        .iter()
+        .min_by_key(|(_, source)| *source != OpSource::Added)
+        .copied()
+        .ok_or_else(|| {
+            ClientError::Other(format!("``{player}`` n'est pas opérateur de ce serveur."))
+        })?;
+
+    match source {
+        OpSource::Default => {
+            return Err(ClientError::Other(format!(
+                "``{player}`` est opérateur de tous les serveurs (``ADMIN_PLAYER``)."
+            )));
+        }
+        OpSource::Owner => {
+            return Err(ClientError::Other(format!(
+                "``{player}`` est le compte Minecraft du propriétaire."
+            )));
+        }
+        OpSource::Added => {}
+    }
+
+    delete(
+        ops_dsl::server_ops
+            .filter(ops_dsl::server_id.eq(id))
+            .filter(ops_dsl::player.eq(player)),
+    )
+    .execute(conn)
+    .await?;
+
+    log::info!("Removed {player} from the ops of server {id}");
+
+    if let Some((_, other)) = matching
+        .iter()
+        .find(|(_, source)| *source != OpSource::Added)
+    {
+        return Ok(format!(
+            "**``{player}`` retiré des opérateurs ajoutés, mais reste opérateur ({}).**",
+            other.label()
+        ));
+    }
+
+    let live = apply_live(ctx, conn, id, status, &[format!("deop {player}")]).await;
+
+    Ok(format!("**``{player}`` n'est plus opérateur{live}**"))
+}
+
+async fn list(conn: &mut PgPooled<'_>, id: i64) -> Result<String, ClientError> {
+    let ops = ops(conn, id).await?;
+
+    if ops.is_empty() {
+        return Ok("Aucun opérateur.".to_owned());
+    }
+
+    Ok(ops
+        .iter()
+        .map(|(player, source)| format!("* ``{player}`` ({})", source.label()))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+pub fn register() -> CreateCommand {
+    let name_option = || {
+        CreateCommandOption::new(CommandOptionType::String, "name", "Le nom du serveur.")
+            .description_localized("en-US", "The name of the server.")
+            .description_localized("en-GB", "The name of the server.")
+            .required(true)
+            .max_length(25)
+    };
+    let player_option = || {
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "player",
+            "Le pseudo Minecraft du joueur.",
+        )
+        .description_localized("en-US", "The Minecraft name of the player.")
+        .description_localized("en-GB", "The Minecraft name of the player.")
+        .required(true)
+        .max_length(16)
+    };
+
+    CreateCommand::new("op")
+        .description("Manage the operators of a server.")
+        .description_localized("en-US", "Manage the operators of a server.")
+        .description_localized("en-GB", "Manage the operators of a server.")
+        .description_localized("fr", "Gère les opérateurs d'un serveur.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "add",
+                "Rend un joueur opérateur.",
+            )
+            .description_localized("en-US", "Make a player operator.")
+            .description_localized("en-GB", "Make a player operator.")
+            .add_sub_option(name_option())
+            .add_sub_option(player_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "remove",
+                "Retire un opérateur.",
+            )
+            .description_localized("en-US", "Remove an operator.")
+            .description_localized("en-GB", "Remove an operator.")
+            .add_sub_option(name_option())
+            .add_sub_option(player_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "Liste les opérateurs.",
+            )
+            .description_localized("en-US", "List the operators.")
+            .description_localized("en-GB", "List the operators.")
+            .add_sub_option(name_option()),
+        )
+}
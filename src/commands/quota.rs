@@ -0,0 +1,106 @@
+use crate::client::error::ClientError;
+use crate::commands::extract_user_optional;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::server::quota::{Quota, usage};
+use crate::util::{EMBED_COLOR, format_memory, get_pool_from_ctx, get_runtime_from_ctx};
+use chrono::TimeDelta;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage,
+};
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let user = extract_user_optional("user", &command.data.options())?.unwrap_or(&command.user);
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let quota = Quota::from_env()?;
+    let usage = usage(
+        &mut conn,
+        get_runtime_from_ctx(ctx).await?.as_ref(),
+        i64::try_from(user.id.get())?,
+        None,
+    )
+    .await?;
+
+    let description = [
+        format!(
+            "* **Serveurs** : {}",
+            limit(
+                &usage.servers.to_string(),
+                quota.max_servers.map(|max| max.to_string())
+            )
+        ),
+        format!(
+            "* **Serveurs lancés** : {}",
+            limit(
+                &usage.running.to_string(),
+                quota.max_running.map(|max| max.to_string())
+            )
+        ),
+        format!(
+            "* **Mémoire des serveurs lancés** : {}",
+            limit(
+                &format_memory(usage.memory),
+                quota.max_memory.map(format_memory)
+            )
+        ),
+        format!(
+            "* **Temps de jeu aujourd'hui** : {}",
+            limit(
+                &format_duration(usage.today),
+                quota.hours_per_day.map(|hours| format!("{hours} h"))
+            )
+        ),
+    ]
+    .join("\n");
+
+    let embed = CreateEmbed::new()
+        .title(format!("Quotas de {}", user.display_name()))
+        .description(description)
+        .color(EMBED_COLOR);
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().add_embed(embed),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+fn limit(used: &str, max: Option<String>) -> String {
+    max.map_or_else(
+        || format!("``{used}`` (illimité)"),
+        |max| format!("``{used}/{max}``"),
+    )
+}
+
+fn format_duration(duration: TimeDelta) -> String {
+    format!(
+        "{} h {:02}",
+        duration.num_hours(),
+        duration.num_minutes() % 60
+    )
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("quota")
+        .description("Show the quotas of a user.")
+        .description_localized("en-US", "Show the quotas of a user.")
+        .description_localized("en-GB", "Show the quotas of a user.")
+        .description_localized("fr", "Affiche les quotas d'un utilisateur.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::User,
+                "user",
+                "L'utilisateur, vous par défaut.",
+            )
+            .description_localized("en-US", "The user, yourself by default.")
+            .description_localized("en-GB", "The user, yourself by default."),
+        )
+}
@@ -0,0 +1,180 @@
+use crate::client::error::ClientError;
+use crate::commands::{extract_bool_optional, extract_str};
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::capacity::check_capacity;
+use crate::server::console::run_command;
+use crate::server::lifecycle::{ServerStatus, boot, halt, transition};
+use crate::server::lock::{lock_server, lock_starts};
+use crate::server::permission::{MemberRole, require};
+use crate::server::quota::check_start;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, get_runtime_from_ctx};
+use diesel::dsl::exists;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use std::time::Duration;
+
+const WARNING_DELAY: Duration = Duration::from_secs(10);
+
+// The restart goes on even if Discord cannot be updated, so that it never stops half-way.
+async fn edit_phase(ctx: &Context, command: &CommandInteraction, description: String) {
+    if let Err(error) = command
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().add_embed(
+                CreateEmbed::new()
+                    .description(description)
+                    .color(EMBED_COLOR),
+            ),
+        )
+        .await
+    {
+        log::warn!("Unable to update the restart message: {error}");
+    }
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let name = extract_str("name", &command.data.options())?.to_lowercase();
+    let warn = extract_bool_optional("warn", &command.data.options())?.unwrap_or(false);
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+    let runtime = get_runtime_from_ctx(ctx).await?;
+
+    let serv_exist: bool = diesel::select(exists(
+        servers_dsl::servers
+            .filter(servers_dsl::name.eq(&name))
+            .filter(servers_dsl::deleted_at.is_null()),
+    ))
+    .get_result(&mut conn)
+    .await?;
+
+    if !serv_exist {
+        return Err(ClientError::OtherStatic("Ce serveur n'existe pas."));
+    }
+
+    let id: i64 = servers_dsl::servers
+        .select(servers_dsl::id)
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await?;
+
+    require(
+        &mut conn,
+        id,
+        command.user.id,
+        command.member.as_deref(),
+        MemberRole::Operator,
+    )
+    .await?;
+
+    let _guard = lock_server(ctx, id).await?;
+
+    let previous = {
+        let _starts = lock_starts(ctx).await?;
+        let status: ServerStatus = servers_dsl::servers
+            .select(servers_dsl::status)
+            .filter(servers_dsl::id.eq(id))
+            .get_result(&mut conn)
+            .await?;
+
+        // A running server already holds its slot, a stopped or crashed one claims a new one.
+        if status != ServerStatus::Running {
+            check_capacity(&mut conn, runtime.as_ref(), id).await?;
+        }
+        check_start(&mut conn, runtime.as_ref(), id).await?;
+        transition(&mut conn, id, ServerStatus::Stopping).await?
+    };
+
+    let embed = CreateEmbed::new()
+        .description(format!(
+            "**Redémarrage du serveur ``{name}`` : arrêt en cours...**"
+        ))
+        .color(EMBED_COLOR);
+
+    if let Err(error) = command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().add_embed(embed),
+            ),
+        )
+        .await
+    {
+        log::warn!("Unable to announce the restart of {name}: {error}");
+    }
+
+    if warn && previous == ServerStatus::Running {
+        edit_phase(
+            ctx,
+            command,
+            format!("**Redémarrage du serveur ``{name}`` : avertissement des joueurs...**"),
+        )
+        .await;
+
+        let message = format!(
+            "say Le serveur redémarre dans {} secondes.",
+            WARNING_DELAY.as_secs()
+        );
+        match run_command(&mut conn, runtime.as_ref(), id, &message).await {
+            Ok(_) => tokio::time::sleep(WARNING_DELAY).await,
+            Err(error) => log::warn!("Unable to warn players of {name}: {error}"),
+        }
+
+        edit_phase(
+            ctx,
+            command,
+            format!("**Redémarrage du serveur ``{name}`` : arrêt en cours...**"),
+        )
+        .await;
+    }
+
+    halt(&mut conn, runtime.as_ref(), id, ServerStatus::Starting).await?;
+
+    edit_phase(
+        ctx,
+        command,
+        format!("**Redémarrage du serveur ``{name}`` : démarrage en cours...**"),
+    )
+    .await;
+
+    boot(&mut conn, runtime.as_ref(), id).await?;
+
+    log::info!("server restarted : {name}!");
+
+    edit_phase(ctx, command, format!("**Serveur ``{name}`` redémarré !**")).await;
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("restart")
+        .description("Restart a server.")
+        .description_localized("en-US", "Restart a server.")
+        .description_localized("en-GB", "Restart a server.")
+        .description_localized("fr", "Redémarre un serveur.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "name",
+                "Le nom du serveur à redémarrer.",
+            )
+            .description_localized("en-US", "The name of the server to restart.")
+            .description_localized("en-GB", "The name of the server to restart.")
+            .required(true)
+            .max_length(25),
+        )
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "warn",
+                "Prévenir les joueurs connectés avant le redémarrage.",
+            )
+            .description_localized("en-US", "Warn online players before restarting.")
+            .description_localized("en-GB", "Warn online players before restarting."),
+        )
+}
@@ -1,10 +1,13 @@
-use std::path::Path;
-
 use crate::client::error::ClientError;
 use crate::commands::extract_str;
 use crate::database::postgresql::{PgPool, PgPooled};
 use crate::database::schemas::servers::dsl as servers_dsl;
-use crate::util::{EMBED_COLOR, get_pool_from_ctx};
+use crate::server::capacity::check_capacity;
+use crate::server::lifecycle::{ServerStatus, boot, transition};
+use crate::server::lock::{lock_server, lock_starts};
+use crate::server::permission::{MemberRole, require};
+use crate::server::quota::check_start;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, get_runtime_from_ctx};
 use diesel::dsl::exists;
 use diesel::{ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
@@ -12,7 +15,6 @@ use serenity::all::{
     CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
     CreateEmbed, CreateInteractionResponseMessage,
 };
-use tokio::process::Command;
 
 pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
     let name = extract_str("name", &command.data.options())?.to_lowercase();
@@ -21,7 +23,9 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), Clie
     let mut conn: PgPooled = pool.get().await?;
 
     let serv_exist: bool = diesel::select(exists(
-        servers_dsl::servers.filter(servers_dsl::name.eq(&name)),
+        servers_dsl::servers
+            .filter(servers_dsl::name.eq(&name))
+            .filter(servers_dsl::deleted_at.is_null()),
     ))
     .get_result(&mut conn)
     .await?;
@@ -30,45 +34,50 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), Clie
         return Err(ClientError::OtherStatic("Ce serveur n'existe pas."));
     }
 
-    let serv_started: bool = diesel::select(exists(
-        servers_dsl::servers.filter(servers_dsl::started.eq(true)),
-    ))
-    .get_result(&mut conn)
+    let id: i64 = servers_dsl::servers
+        .select(servers_dsl::id)
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await?;
+
+    require(
+        &mut conn,
+        id,
+        command.user.id,
+        command.member.as_deref(),
+        MemberRole::Operator,
+    )
     .await?;
 
-    if serv_started {
-        return Err(ClientError::OtherStatic("Un serveur est déjà lancé."));
+    let runtime = get_runtime_from_ctx(ctx).await?;
+    let _guard = lock_server(ctx, id).await?;
+
+    {
+        let _starts = lock_starts(ctx).await?;
+        check_capacity(&mut conn, runtime.as_ref(), id).await?;
+        check_start(&mut conn, runtime.as_ref(), id).await?;
+        transition(&mut conn, id, ServerStatus::Starting).await?;
     }
 
     let embed = CreateEmbed::new()
         .description(format!("**Démarrage du serveur ``{name}`` ...**"))
         .color(EMBED_COLOR);
 
-    command
+    if let Err(error) = command
         .create_response(
             &ctx.http,
             serenity::builder::CreateInteractionResponse::Message(
                 CreateInteractionResponseMessage::new().add_embed(embed),
             ),
         )
-        .await?;
-
-    let id: i64 = servers_dsl::servers
-        .select(servers_dsl::id)
-        .filter(servers_dsl::name.eq(&name))
-        .get_result(&mut conn)
-        .await?;
-
-    Command::new("docker")
-        .args(["compose", "up", "-d", "--wait"])
-        .current_dir(Path::new("worlds").join(id.to_string()))
-        .status()
-        .await?;
+        .await
+    {
+        transition(&mut conn, id, ServerStatus::Crashed).await?;
+        return Err(error.into());
+    }
 
-    diesel::update(servers_dsl::servers.filter(servers_dsl::name.eq(&name)))
-        .set(servers_dsl::started.eq(true))
-        .execute(&mut conn)
-        .await?;
+    boot(&mut conn, runtime.as_ref(), id).await?;
 
     log::info!("server started : {name}!");
 
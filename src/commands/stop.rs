@@ -1,10 +1,11 @@
-use std::path::Path;
-
 use crate::client::error::ClientError;
 use crate::commands::extract_str;
 use crate::database::postgresql::{PgPool, PgPooled};
 use crate::database::schemas::servers::dsl as servers_dsl;
-use crate::util::{EMBED_COLOR, get_pool_from_ctx};
+use crate::server::lifecycle::{ServerStatus, halt, transition};
+use crate::server::lock::lock_server;
+use crate::server::permission::{MemberRole, require};
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, get_runtime_from_ctx};
 use diesel::dsl::exists;
 use diesel::{ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
@@ -12,7 +13,6 @@ use serenity::all::{
     CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
     CreateEmbed, CreateInteractionResponseMessage,
 };
-use tokio::process::Command;
 
 pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
     let name = extract_str("name", &command.data.options())?.to_lowercase();
@@ -21,7 +21,9 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), Clie
     let mut conn: PgPooled = pool.get().await?;
 
     let serv_exist: bool = diesel::select(exists(
-        servers_dsl::servers.filter(servers_dsl::name.eq(&name)),
+        servers_dsl::servers
+            .filter(servers_dsl::name.eq(&name))
+            .filter(servers_dsl::deleted_at.is_null()),
     ))
     .get_result(&mut conn)
     .await?;
@@ -30,17 +32,25 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), Clie
         return Err(ClientError::OtherStatic("Ce serveur n'existe pas."));
     }
 
-    let serv_started: bool = diesel::select(exists(
-        servers_dsl::servers
-            .filter(servers_dsl::started.eq(true))
-            .filter(servers_dsl::name.eq(&name)),
-    ))
-    .get_result(&mut conn)
+    let id: i64 = servers_dsl::servers
+        .select(servers_dsl::id)
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await?;
+
+    require(
+        &mut conn,
+        id,
+        command.user.id,
+        command.member.as_deref(),
+        MemberRole::Operator,
+    )
     .await?;
 
-    if !serv_started {
-        return Err(ClientError::OtherStatic("Le serveur n'est pas lancé."));
-    }
+    let _guard = lock_server(ctx, id).await?;
+
+    transition(&mut conn, id, ServerStatus::Stopping).await?;
 
     let embed = CreateEmbed::new()
         .description(format!("**Arrêt du serveur ``{name}`` en cours...**"))
@@ -55,22 +65,13 @@ pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), Clie
         )
         .await?;
 
-    let id: i64 = servers_dsl::servers
-        .select(servers_dsl::id)
-        .filter(servers_dsl::name.eq(&name))
-        .get_result(&mut conn)
-        .await?;
-
-    Command::new("docker")
-        .args(["compose", "down"])
-        .current_dir(Path::new("worlds").join(id.to_string()))
-        .status()
-        .await?;
-
-    diesel::update(servers_dsl::servers.filter(servers_dsl::name.eq(&name)))
-        .set(servers_dsl::started.eq(false))
-        .execute(&mut conn)
-        .await?;
+    halt(
+        &mut conn,
+        get_runtime_from_ctx(ctx).await?.as_ref(),
+        id,
+        ServerStatus::Stopped,
+    )
+    .await?;
 
     log::info!("server stoped : {name}!");
 
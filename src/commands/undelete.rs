@@ -0,0 +1,133 @@
+use crate::client::error::ClientError;
+use crate::commands::extract_str;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::capacity::memory_in;
+use crate::server::lock::lock_server;
+use crate::server::permission::{MemberRole, require};
+use crate::server::quota::check_create;
+use crate::server::trash::{trash_dir, untrash_server};
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, get_runtime_from_ctx};
+use diesel::dsl::exists;
+use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, UserId,
+};
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let name = extract_str("name", &command.data.options())?.to_lowercase();
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let (id, port, rcon_port, owner_id): (i64, i64, Option<i64>, Option<i64>) =
+        servers_dsl::servers
+            .select((
+                servers_dsl::id,
+                servers_dsl::port,
+                servers_dsl::rcon_port,
+                servers_dsl::owner_id,
+            ))
+            .filter(servers_dsl::name.eq(&name))
+            .filter(servers_dsl::deleted_at.is_not_null())
+            .order_by(servers_dsl::deleted_at.desc())
+            .first(&mut conn)
+            .await
+            .optional()?
+            .ok_or(ClientError::OtherStatic(
+                "Aucun serveur de ce nom dans la corbeille.",
+            ))?;
+
+    require(
+        &mut conn,
+        id,
+        command.user.id,
+        command.member.as_deref(),
+        MemberRole::Operator,
+    )
+    .await?;
+
+    let name_taken: bool = diesel::select(exists(
+        servers_dsl::servers
+            .filter(servers_dsl::name.eq(&name))
+            .filter(servers_dsl::deleted_at.is_null()),
+    ))
+    .get_result(&mut conn)
+    .await?;
+    if name_taken {
+        return Err(ClientError::OtherStatic(
+            "Un autre serveur porte déjà ce nom.",
+        ));
+    }
+
+    let ports: Vec<i64> = std::iter::once(port).chain(rcon_port).collect();
+    let port_taken: bool = diesel::select(exists(
+        servers_dsl::servers
+            .filter(servers_dsl::deleted_at.is_null())
+            .filter(
+                servers_dsl::port
+                    .eq_any(&ports)
+                    .or(servers_dsl::rcon_port.eq_any(&ports)),
+            ),
+    ))
+    .get_result(&mut conn)
+    .await?;
+    if port_taken {
+        return Err(ClientError::OtherStatic(
+            "Les ports de ce serveur ont été réattribués à un autre serveur.",
+        ));
+    }
+
+    // The restored server counts again against the quota of its owner.
+    if let Some(owner_id) = owner_id {
+        check_create(
+            &mut conn,
+            get_runtime_from_ctx(ctx).await?.as_ref(),
+            UserId::new(owner_id.cast_unsigned()),
+            memory_in(&trash_dir(id)).await?,
+        )
+        .await?;
+    }
+
+    let _guard = lock_server(ctx, id).await?;
+
+    untrash_server(&mut conn, id).await?;
+
+    log::info!("Restored server {name} from the trash!");
+
+    let embed = CreateEmbed::new()
+        .description(format!("**Serveur ``{name}`` restauré !**"))
+        .color(EMBED_COLOR);
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().add_embed(embed),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new("undelete")
+        .description("Restore a deleted server.")
+        .description_localized("en-US", "Restore a deleted server.")
+        .description_localized("en-GB", "Restore a deleted server.")
+        .description_localized("fr", "Restaure un serveur supprimé.")
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "name",
+                "Le nom du serveur à restaurer.",
+            )
+            .description_localized("en-US", "The name of the server to restore.")
+            .description_localized("en-GB", "The name of the server to restore.")
+            .required(true)
+            .max_length(25),
+        )
+}
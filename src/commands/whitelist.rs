@@ -0,0 +1,209 @@
+use crate::client::error::ClientError;
+use crate::commands::{apply_live, extract_str, extract_subcommand};
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::server_whitelist::dsl as whitelist_dsl;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::lifecycle::ServerStatus;
+use crate::server::permission::{MemberRole, require};
+use crate::server::whitelist::{is_valid_player, players};
+use crate::util::{EMBED_COLOR, get_pool_from_ctx};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, delete, insert_into};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateInteractionResponseMessage, ResolvedOption,
+};
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<(), ClientError> {
+    let options = command.data.options();
+    let (subcommand, options) = extract_subcommand(&options)?;
+    let name = extract_str("name", &options)?.to_lowercase();
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let (id, status): (i64, ServerStatus) = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::status))
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Ce serveur n'existe pas."))?;
+
+    if subcommand != "list" {
+        require(
+            &mut conn,
+            id,
+            command.user.id,
+            command.member.as_deref(),
+            MemberRole::Operator,
+        )
+        .await?;
+    }
+
+    let description = match subcommand {
+        "add" => add(ctx, &mut conn, id, status, &options).await?,
+        "remove" => remove(ctx, &mut conn, id, status, &options).await?,
+        "list" => list(&mut conn, id).await?,
+        _ => return Err(ClientError::OtherStatic("Sous-commande inconnue.")),
+    };
+
+    let embed = CreateEmbed::new()
+        .title(format!("Liste blanche de ``{name}``"))
+        .description(description)
+        .color(EMBED_COLOR);
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::builder::CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().add_embed(embed),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn add(
+    ctx: &Context,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    status: ServerStatus,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, ClientError> {
+    let player = extract_str("player", options)?;
+    if !is_valid_player(player) {
+        return Err(ClientError::Other(format!(
+            "``{player}`` n'est pas un pseudo Minecraft valide."
+        )));
+    }
+
+    let players = players(conn, id).await?;
+    if players
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(player))
+    {
+        return Err(ClientError::Other(format!(
+            "``{player}`` est déjà dans la liste blanche."
+        )));
+    }
+
+    insert_into(whitelist_dsl::server_whitelist)
+        .values((
+            whitelist_dsl::server_id.eq(id),
+            whitelist_dsl::player.eq(player),
+        ))
+        .execute(conn)
+        .await?;
+
+    log::info!("Whitelisted {player} on server {id}");
+
+    // The first player turns the whitelist on, closing the server to everyone else.
+    let mut commands = vec![format!("whitelist add {player}")];
+    if players.is_empty() {
+        commands.push("whitelist on".to_owned());
+    }
+    let live = apply_live(ctx, conn, id, status, &commands).await;
+
+    Ok(format!("**``{player}`` ajouté à la liste blanche{live}**"))
+}
+
+async fn remove(
+    ctx: &Context,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    status: ServerStatus,
+    options: &[ResolvedOption<'_>],
+) -> Result<String, ClientError> {
+    let player = extract_str("player", options)?;
+
+    let players = players(conn, id).await?;
+    let player = players
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(player))
+        .ok_or_else(|| {
+            ClientError::Other(format!("``{player}`` n'est pas dans la liste blanche."))
+        })?;
+
+    delete(
+        whitelist_dsl::server_whitelist
+            .filter(whitelist_dsl::server_id.eq(id))
+            .filter(whitelist_dsl::player.eq(player)),
+    )
+    .execute(conn)
+    .await?;
+
+    log::info!("Removed {player} from the whitelist of server {id}");
+
+    // Without anyone left in it, the whitelist is turned off and the server opens again.
+    let mut commands = vec![format!("whitelist remove {player}")];
+    if players.len() == 1 {
+        commands.push("whitelist off".to_owned());
+    }
+    let live = apply_live(ctx, conn, id, status, &commands).await;
+
+    Ok(format!("**``{player}`` retiré de la liste blanche{live}**"))
+}
+
+async fn list(conn: &mut PgPooled<'_>, id: i64) -> Result<String, ClientError> {
+    let players = players(conn, id).await?;
+
+    if players.is_empty() {
+        return Ok("Liste blanche vide, le serveur est ouvert à tous.".to_owned());
+    }
+
+    Ok(players
+        .iter()
+        .map(|player| format!("* ``{player}``"))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+pub fn register() -> CreateCommand {
+    let name_option = || {
+        CreateCommandOption::new(CommandOptionType::String, "name", "Le nom du serveur.")
+            .description_localized("en-US", "The name of the server.")
+            .description_localized("en-GB", "The name of the server.")
+            .required(true)
+            .max_length(25)
+    };
+    let player_option = || {
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "player",
+            "Le pseudo Minecraft du joueur.",
+        )
+        .description_localized("en-US", "The Minecraft name of the player.")
+        .description_localized("en-GB", "The Minecraft name of the player.")
+        .required(true)
+        .max_length(16)
+    };
+
+    CreateCommand::new("whitelist")
+        .description("Manage the whitelist of a server.")
+        .description_localized("en-US", "Manage the whitelist of a server.")
+        .description_localized("en-GB", "Manage the whitelist of a server.")
+        .description_localized("fr", "Gère la liste blanche d'un serveur.")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Ajoute un joueur.")
+                .description_localized("en-US", "Add a player.")
+                .description_localized("en-GB", "Add a player.")
+                .add_sub_option(name_option())
+                .add_sub_option(player_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Retire un joueur.")
+                .description_localized("en-US", "Remove a player.")
+                .description_localized("en-GB", "Remove a player.")
+                .add_sub_option(name_option())
+                .add_sub_option(player_option()),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "list", "Liste les joueurs.")
+                .description_localized("en-US", "List the players.")
+                .description_localized("en-GB", "List the players.")
+                .add_sub_option(name_option()),
+        )
+}
@@ -0,0 +1,13 @@
+use crate::database::schemas::server_addons;
+use diesel::{Queryable, Selectable};
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = server_addons)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ServerAddons {
+    pub id: i64,
+    pub server_id: i64,
+    pub kind: String,
+    pub source: String,
+    pub value: String,
+}
@@ -0,0 +1,17 @@
+use crate::database::schemas::audit_events;
+use chrono::{DateTime, Utc};
+use diesel::{Queryable, Selectable};
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = audit_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuditEvents {
+    pub id: i64,
+    pub user_id: i64,
+    pub action: String,
+    pub server_name: Option<String>,
+    pub parameters: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
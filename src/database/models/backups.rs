@@ -0,0 +1,15 @@
+use crate::database::schemas::backups;
+use chrono::{DateTime, Utc};
+use diesel::{Queryable, Selectable};
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = backups)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Backups {
+    pub id: i64,
+    pub server_id: i64,
+    pub file_name: String,
+    pub size: i64,
+    pub kind: String,
+    pub created_at: DateTime<Utc>,
+}
@@ -1 +1,4 @@
+pub mod addons;
+pub mod audit_events;
+pub mod backups;
 pub mod servers;
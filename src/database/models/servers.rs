@@ -1,4 +1,7 @@
 use crate::database::schemas::servers;
+use crate::server::lifecycle::ServerStatus;
+use crate::server::software::ServerType;
+use chrono::{DateTime, Utc};
 use diesel::{Queryable, Selectable};
 
 #[derive(Queryable, Selectable, Debug, Clone)]
@@ -10,5 +13,10 @@ pub struct Servers {
     pub version: String,
     pub difficulty: String,
     pub port: i64,
-    pub started: bool,
+    pub status: ServerStatus,
+    pub rcon_port: Option<i64>,
+    pub rcon_password: Option<String>,
+    pub server_type: ServerType,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub owner_id: Option<i64>,
 }
@@ -62,7 +62,7 @@ pub async fn run_migration() {
             &std::env::var("DATABASE_URL").unwrap(),
         )
         .unwrap_or_else(|error| {
-            log::error!("Error when connecting to database to deploy migrations: {error}",);
+            log::error!("Error when connecting to database to deploy migrations: {error}");
             std::process::exit(1);
         });
         conn.run_pending_migrations(MIGRATIONS)
@@ -1,10 +1,100 @@
 diesel::table! {
-    servers (name) {
+    servers (id) {
         id -> BigSerial,
         name -> Text,
         version -> Text,
         difficulty -> Text,
         port -> BigInt,
-        started -> Bool
+        status -> Text,
+        rcon_port -> Nullable<BigInt>,
+        rcon_password -> Nullable<Text>,
+        server_type -> Text,
+        deleted_at -> Nullable<Timestamptz>,
+        owner_id -> Nullable<BigInt>
+    }
+}
+
+diesel::table! {
+    server_members (server_id, user_id) {
+        server_id -> BigInt,
+        user_id -> BigInt,
+        role -> Text
+    }
+}
+
+diesel::table! {
+    server_addons (id) {
+        id -> BigSerial,
+        server_id -> BigInt,
+        kind -> Text,
+        source -> Text,
+        value -> Text
+    }
+}
+
+diesel::table! {
+    backups (id) {
+        id -> BigSerial,
+        server_id -> BigInt,
+        file_name -> Text,
+        size -> BigInt,
+        kind -> Text,
+        created_at -> Timestamptz
+    }
+}
+
+diesel::table! {
+    backup_restores (id) {
+        id -> BigSerial,
+        server_id -> BigInt,
+        backup_id -> Nullable<BigInt>,
+        safety_backup_id -> Nullable<BigInt>,
+        user_id -> BigInt,
+        restored_at -> Timestamptz
+    }
+}
+
+diesel::table! {
+    server_sessions (id) {
+        id -> BigSerial,
+        server_id -> BigInt,
+        started_at -> Timestamptz,
+        stopped_at -> Nullable<Timestamptz>
+    }
+}
+
+diesel::table! {
+    audit_events (id) {
+        id -> BigSerial,
+        user_id -> BigInt,
+        action -> Text,
+        server_name -> Nullable<Text>,
+        parameters -> Text,
+        success -> Bool,
+        error -> Nullable<Text>,
+        created_at -> Timestamptz
+    }
+}
+
+diesel::table! {
+    server_whitelist (id) {
+        id -> BigSerial,
+        server_id -> BigInt,
+        player -> Text
+    }
+}
+
+diesel::table! {
+    minecraft_accounts (user_id) {
+        user_id -> BigInt,
+        player -> Text
+    }
+}
+
+diesel::table! {
+    server_ops (id) {
+        id -> BigSerial,
+        server_id -> BigInt,
+        player -> Text
     }
 }
@@ -0,0 +1,72 @@
+use crate::client::error::ClientError;
+use crate::commands::{extract_str, extract_subcommand};
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::backup::{BackupKind, list_backups};
+use crate::util::{format_size, get_pool_from_ctx};
+use chrono::Local;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::{CommandInteraction, Context, CreateAutocompleteResponse, ResolvedValue};
+
+pub async fn autocomplete_backup(
+    ctx: Context,
+    command: CommandInteraction,
+) -> Result<(), ClientError> {
+    let options = command.data.options();
+    let (_, options) = extract_subcommand(&options)?;
+    let name = extract_str("name", &options)?.to_lowercase();
+    let typed = options
+        .iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::Autocomplete { value, .. } => Some(value),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let pool: PgPool = get_pool_from_ctx(&ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let id: Option<i64> = servers_dsl::servers
+        .select(servers_dsl::id)
+        .filter(servers_dsl::name.eq(&name))
+        .filter(servers_dsl::deleted_at.is_null())
+        .get_result(&mut conn)
+        .await
+        .optional()?;
+
+    let backups = match id {
+        Some(id) => list_backups(&mut conn, id).await?,
+        None => Vec::new(),
+    };
+
+    let mut auto_complete = CreateAutocompleteResponse::new();
+
+    for backup in backups
+        .iter()
+        .filter(|backup| backup.id.to_string().starts_with(typed))
+        .take(25)
+    {
+        auto_complete = auto_complete.add_int_choice(
+            format!(
+                "#{} {} — {} ({})",
+                backup.id,
+                backup
+                    .created_at
+                    .with_timezone(&Local)
+                    .format("%d/%m/%Y %H:%M"),
+                format_size(backup.size.cast_unsigned()),
+                BackupKind::label(&backup.kind)
+            ),
+            backup.id,
+        );
+    }
+
+    command
+        .create_response(
+            &ctx.http,
+            serenity::all::CreateInteractionResponse::Autocomplete(auto_complete),
+        )
+        .await?;
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::client::error::ClientError;
+use crate::server::software::ServerType;
 use serenity::all::{CommandInteraction, Context, CreateAutocompleteResponse, ResolvedValue};
 use tokio::fs;
 
@@ -9,6 +10,14 @@ pub async fn autocomplete_version(
     let json = fs::read_to_string("versions.json").await?;
     let versions: Vec<String> = serde_json::from_str(&json)?;
     let options = command.data.options();
+    let server_type = options
+        .iter()
+        .find(|opt| opt.name == "type")
+        .and_then(|opt| match opt.value {
+            ResolvedValue::String(value) => value.parse::<ServerType>().ok(),
+            _ => None,
+        })
+        .unwrap_or(ServerType::Vanilla);
     let opt = &options
         .iter()
         .find(|opt| opt.name == "version")
@@ -21,7 +30,7 @@ pub async fn autocomplete_version(
         } => {
             let a: Vec<String> = versions
                 .into_iter()
-                .filter(|ver| ver.contains(str))
+                .filter(|ver| ver.contains(str) && server_type.supports_version(ver))
                 .take(25)
                 .collect();
 
@@ -0,0 +1,23 @@
+use crate::commands::audit::{AuditFilter, get_page};
+use serenity::all::{ComponentInteraction, Context, CreateEmbed, EditInteractionResponse};
+
+pub async fn button_audit(ctx: Context, component: ComponentInteraction) {
+    let (page, filter) = AuditFilter::from_custom_id(&component.data.custom_id);
+
+    if let Err(e) = component.defer(&ctx.http).await {
+        log::error!("Failed to defer interaction: {e}");
+        return;
+    }
+    if let Err(e) = component
+        .edit_response(
+            &ctx,
+            get_page(&ctx, page, &filter).await.unwrap_or_else(|error| {
+                EditInteractionResponse::new()
+                    .embed(CreateEmbed::new().description(error.to_string()))
+            }),
+        )
+        .await
+    {
+        log::error!("Failed to edit interaction response: {e}");
+    }
+}
@@ -0,0 +1,264 @@
+use crate::client::access::get_access_from_ctx;
+use crate::client::error::ClientError;
+use crate::commands;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::backups::dsl as backups_dsl;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::archive::{dir_size, world_dirs};
+use crate::server::audit::{AuditEntry, record};
+use crate::server::permission::{MemberRole, require};
+use crate::util::{EMBED_COLOR, format_size, get_pool_from_ctx, get_time, world_dir};
+use chrono::{DateTime, Local};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    ButtonStyle, CommandInteraction, ComponentInteraction, Context, CreateButton, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+};
+use std::time::Duration;
+
+const EXPIRY: Duration = Duration::from_mins(2);
+
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    Delete(i64),
+    Restore(i64, i64),
+}
+
+impl Action {
+    fn encode(self) -> String {
+        match self {
+            Self::Delete(id) => format!("delete-{id}"),
+            Self::Restore(id, backup_id) => format!("restore-{id}-{backup_id}"),
+        }
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let mut parts = value.split('-');
+        let action = match parts.next()? {
+            "delete" => Self::Delete(parts.next()?.parse().ok()?),
+            "restore" => Self::Restore(parts.next()?.parse().ok()?, parts.next()?.parse().ok()?),
+            _ => return None,
+        };
+        parts.next().is_none().then_some(action)
+    }
+
+    // Command the confirmation was asked by, whose access rules still apply to the click.
+    const fn command(self) -> &'static str {
+        match self {
+            Self::Delete(_) => "delete",
+            Self::Restore(..) => "backup",
+        }
+    }
+
+    const fn server_id(self) -> i64 {
+        match self {
+            Self::Delete(id) | Self::Restore(id, _) => id,
+        }
+    }
+
+    fn audit_entry(self, server_name: Option<String>) -> AuditEntry {
+        let (action, parameters) = match self {
+            Self::Delete(_) => ("delete", String::new()),
+            Self::Restore(_, backup_id) => ("backup restore", format!("backup={backup_id}")),
+        };
+        AuditEntry {
+            action: format!("{action} (confirmé)"),
+            server_name,
+            parameters,
+        }
+    }
+}
+
+// What a destructive action is about to overwrite, shown before asking for confirmation.
+pub async fn loss_summary(conn: &mut PgPooled<'_>, id: i64) -> Result<String, ClientError> {
+    let backups: i64 = backups_dsl::backups
+        .filter(backups_dsl::server_id.eq(id))
+        .count()
+        .get_result(conn)
+        .await?;
+
+    let data_dir = world_dir(id).join("data");
+    let (size, last_played) = tokio::task::spawn_blocking(move || {
+        let worlds = world_dirs(&data_dir);
+        let size: u64 = worlds.iter().map(|world| dir_size(world)).sum();
+        // `level.dat` is rewritten on every save, so it tells when the world was last played.
+        let last_played = worlds
+            .iter()
+            .filter_map(|world| world.join("level.dat").metadata().ok()?.modified().ok())
+            .max();
+        (size, last_played)
+    })
+    .await
+    .map_err(|error| ClientError::Other(error.to_string()))?;
+
+    let last_played = last_played.map_or_else(
+        || "jamais".to_owned(),
+        |time| {
+            DateTime::<Local>::from(time)
+                .format("%d/%m/%Y %H:%M")
+                .to_string()
+        },
+    );
+
+    Ok(format!(
+        "* **Taille du monde** : ``{}``\n* **Dernière partie** : ``{last_played}``\n* **Sauvegardes** : ``{backups}``",
+        format_size(size)
+    ))
+}
+
+// Replies with an ephemeral summary and the buttons, the action itself only runs on confirmation.
+pub async fn ask(
+    ctx: &Context,
+    command: &CommandInteraction,
+    action: Action,
+    title: String,
+    summary: String,
+) -> Result<(), ClientError> {
+    let expires = get_time()? + EXPIRY.as_secs();
+
+    let embed = CreateEmbed::new()
+        .title(title)
+        .description(format!(
+            "{summary}\n\nCette demande expire <t:{expires}:R>."
+        ))
+        .color(EMBED_COLOR);
+
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .ephemeral(true)
+                    .add_embed(embed)
+                    .button(
+                        CreateButton::new(format!("confirm-{expires}-{}", action.encode()))
+                            .label("Confirmer")
+                            .style(ButtonStyle::Danger),
+                    )
+                    .button(
+                        CreateButton::new("confirm-cancel")
+                            .label("Annuler")
+                            .style(ButtonStyle::Secondary),
+                    ),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn button_confirm(ctx: Context, component: ComponentInteraction) {
+    if let Err(error) = confirm(&ctx, &component).await {
+        let embed = CreateEmbed::new()
+            .description(error.to_string())
+            .color(EMBED_COLOR);
+
+        if update(&ctx, &component, embed.clone()).await.is_err()
+            && let Err(err) = component
+                .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
+                .await
+        {
+            log::error!("Cannot respond to confirmation: {err}");
+        }
+    }
+}
+
+async fn confirm(ctx: &Context, component: &ComponentInteraction) -> Result<(), ClientError> {
+    let value = component
+        .data
+        .custom_id
+        .strip_prefix("confirm-")
+        .unwrap_or_default();
+
+    if value == "cancel" {
+        return update(ctx, component, message("Opération annulée.")).await;
+    }
+
+    let (expires, action) = value
+        .split_once('-')
+        .and_then(|(expires, action)| Some((expires.parse::<u64>().ok()?, Action::decode(action)?)))
+        .ok_or(ClientError::OtherStatic("Bouton invalide."))?;
+
+    if get_time()? > expires {
+        return update(
+            ctx,
+            component,
+            message("Cette demande a expiré, relancez la commande."),
+        )
+        .await;
+    }
+
+    // The member may have lost their role since the confirmation was asked.
+    get_access_from_ctx(ctx)
+        .await?
+        .check(action.command(), component.member.as_ref())?;
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+    require(
+        &mut conn,
+        action.server_id(),
+        component.user.id,
+        component.member.as_ref(),
+        MemberRole::Operator,
+    )
+    .await?;
+    let server_name: Option<String> = servers_dsl::servers
+        .select(servers_dsl::name)
+        .filter(servers_dsl::id.eq(action.server_id()))
+        .get_result(&mut conn)
+        .await
+        .optional()?;
+    drop(conn);
+
+    update(ctx, component, message("Opération en cours...")).await?;
+
+    let result = match action {
+        Action::Delete(id) => commands::delete::delete_server(ctx, id).await,
+        Action::Restore(id, backup_id) => {
+            commands::backup::restore_server(ctx, id, backup_id, component.user.id.get()).await
+        }
+    };
+    record(
+        ctx,
+        component.user.id,
+        &action.audit_entry(server_name),
+        result.as_ref().err(),
+    )
+    .await;
+    let description = result?;
+
+    component
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().embed(message(&description)),
+        )
+        .await?;
+
+    Ok(())
+}
+
+fn message(description: &str) -> CreateEmbed {
+    CreateEmbed::new()
+        .description(description)
+        .color(EMBED_COLOR)
+}
+
+// Replaces the confirmation itself, removing its buttons.
+async fn update(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    embed: CreateEmbed,
+) -> Result<(), ClientError> {
+    component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .components(Vec::new()),
+            ),
+        )
+        .await?;
+    Ok(())
+}
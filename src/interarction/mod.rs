@@ -1,2 +1,5 @@
+pub mod autocomplete_backup;
 pub mod autocomplete_version;
+pub mod button_audit;
 pub mod button_list;
+pub mod confirm;
@@ -15,4 +15,8 @@ pub mod client;
 pub mod commands;
 pub mod database;
 pub mod interarction;
+pub mod protocol;
+pub mod runtime;
+pub mod server;
+pub mod tasks;
 pub mod util;
@@ -16,7 +16,6 @@ async fn main() -> Result<(), Box<dyn Error>> {
         "DISCORD_APP_ID",
         "DISCORD_GUILD_ID",
         "DATABASE_URL",
-        "ADMIN_PLAYER",
     ]);
 
     log::info!("Start bot-mc...");
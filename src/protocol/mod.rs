@@ -0,0 +1,2 @@
+pub mod rcon;
+pub mod slp;
@@ -0,0 +1,189 @@
+use crate::client::error::ClientError;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const TYPE_RESPONSE: i32 = 0;
+const TYPE_COMMAND: i32 = 2;
+const TYPE_AUTH: i32 = 3;
+const MAX_PACKET_LENGTH: i32 = 4096 + 10;
+
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconClient {
+    pub async fn connect(host: &str, port: u16, password: &str) -> Result<Self, ClientError> {
+        let mut client = Self {
+            stream: TcpStream::connect((host, port)).await?,
+            next_id: 1,
+        };
+
+        let id = client.send(TYPE_AUTH, password).await?;
+        // The server answers the authentication with an empty response value first.
+        loop {
+            let (response_id, kind, _) = client.receive().await?;
+            if response_id == -1 {
+                return Err(ClientError::ProtocolError(
+                    "Mot de passe RCON refusé.".to_owned(),
+                ));
+            }
+            if response_id == id && kind == TYPE_COMMAND {
+                return Ok(client);
+            }
+        }
+    }
+
+    pub async fn command(&mut self, command: &str) -> Result<String, ClientError> {
+        let id = self.send(TYPE_COMMAND, command).await?;
+        // Long outputs are split over several packets, the answer to this unknown request
+        // type arrives right after the last of them.
+        let marker = self.send(TYPE_RESPONSE, "").await?;
+
+        let mut output = String::new();
+        loop {
+            let (response_id, _, body) = self.receive().await?;
+            if response_id == marker {
+                return Ok(output);
+            }
+            if response_id == id {
+                output.push_str(&body);
+            }
+        }
+    }
+
+    async fn send(&mut self, kind: i32, body: &str) -> Result<i32, ClientError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let length = i32::try_from(body.len() + 10)?;
+        let mut packet = Vec::with_capacity(body.len() + 14);
+        packet.extend_from_slice(&length.to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+
+        self.stream.write_all(&packet).await?;
+        Ok(id)
+    }
+
+    async fn receive(&mut self) -> Result<(i32, i32, String), ClientError> {
+        let length = self.stream.read_i32_le().await?;
+        if !(10..=MAX_PACKET_LENGTH).contains(&length) {
+            return Err(ClientError::ProtocolError(format!(
+                "Taille de paquet RCON invalide : {length}."
+            )));
+        }
+
+        let mut packet = vec![0; length as usize];
+        self.stream.read_exact(&mut packet).await?;
+
+        let id = i32::from_le_bytes([packet[0], packet[1], packet[2], packet[3]]);
+        let kind = i32::from_le_bytes([packet[4], packet[5], packet[6], packet[7]]);
+        let body = String::from_utf8_lossy(&packet[8..packet.len() - 2]).into_owned();
+
+        Ok((id, kind, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    async fn read_packet(stream: &mut TcpStream) -> (i32, i32, String) {
+        let length = stream.read_i32_le().await.unwrap();
+        let mut packet = vec![0; usize::try_from(length).unwrap()];
+        stream.read_exact(&mut packet).await.unwrap();
+        assert_eq!(packet[packet.len() - 2..], [0, 0], "missing terminators");
+
+        let id = i32::from_le_bytes([packet[0], packet[1], packet[2], packet[3]]);
+        let kind = i32::from_le_bytes([packet[4], packet[5], packet[6], packet[7]]);
+        let body = String::from_utf8(packet[8..packet.len() - 2].to_vec()).unwrap();
+        (id, kind, body)
+    }
+
+    async fn write_packet(stream: &mut TcpStream, id: i32, kind: i32, body: &str) {
+        let mut packet = i32::try_from(body.len() + 10)
+            .unwrap()
+            .to_le_bytes()
+            .to_vec();
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        stream.write_all(&packet).await.unwrap();
+    }
+
+    // Accepts one connection, checks the password and answers the authentication like Minecraft.
+    async fn fake_server<F, Fut>(accept: bool, then: F) -> u16
+    where
+        F: FnOnce(TcpStream) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (id, kind, password) = read_packet(&mut stream).await;
+            assert_eq!((kind, password.as_str()), (TYPE_AUTH, "secret"));
+
+            write_packet(&mut stream, id, TYPE_RESPONSE, "").await;
+            write_packet(&mut stream, if accept { id } else { -1 }, TYPE_COMMAND, "").await;
+            then(stream).await;
+        });
+
+        port
+    }
+
+    #[tokio::test]
+    async fn refused_password() {
+        let port = fake_server(false, |_| async {}).await;
+        let error = RconClient::connect("127.0.0.1", port, "secret")
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("Mot de passe RCON refusé"));
+    }
+
+    #[tokio::test]
+    async fn command_joins_split_output() {
+        let port = fake_server(true, |mut stream| async move {
+            let (id, kind, command) = read_packet(&mut stream).await;
+            assert_eq!((kind, command.as_str()), (TYPE_COMMAND, "list"));
+            let (marker, kind, body) = read_packet(&mut stream).await;
+            assert_eq!((kind, body.as_str()), (TYPE_RESPONSE, ""));
+
+            write_packet(&mut stream, id, TYPE_RESPONSE, "There are 2 ").await;
+            write_packet(&mut stream, 99, TYPE_RESPONSE, "stray").await;
+            write_packet(&mut stream, id, TYPE_RESPONSE, "players online").await;
+            write_packet(&mut stream, marker, TYPE_RESPONSE, "Unknown request 0").await;
+        })
+        .await;
+
+        let mut client = RconClient::connect("127.0.0.1", port, "secret")
+            .await
+            .unwrap();
+        assert_eq!(
+            client.command("list").await.unwrap(),
+            "There are 2 players online"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_packet_lengths() {
+        let port = fake_server(true, |mut stream| async move {
+            read_packet(&mut stream).await;
+            read_packet(&mut stream).await;
+            stream.write_all(&5_i32.to_le_bytes()).await.unwrap();
+        })
+        .await;
+
+        let mut client = RconClient::connect("127.0.0.1", port, "secret")
+            .await
+            .unwrap();
+        assert!(client.command("list").await.is_err());
+    }
+}
@@ -0,0 +1,309 @@
+use crate::client::error::ClientError;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+
+// Status responses are JSON documents far below this size, anything bigger is garbage.
+const MAX_PACKET_LENGTH: i32 = 1 << 21;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Players {
+    pub max: i64,
+    pub online: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Version {
+    pub name: String,
+    pub protocol: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Status {
+    pub version: Version,
+    pub players: Players,
+    #[serde(default)]
+    pub description: Value,
+    #[serde(skip)]
+    pub latency: Duration,
+}
+
+impl Status {
+    #[must_use]
+    pub fn motd(&self) -> String {
+        let mut motd = String::new();
+        flatten_text(&self.description, &mut motd);
+
+        // Drop the legacy `§x` formatting codes, they are meaningless outside of the game.
+        let mut chars = motd.chars();
+        let mut plain = String::with_capacity(motd.len());
+        while let Some(c) = chars.next() {
+            if c == '§' {
+                chars.next();
+            } else {
+                plain.push(c);
+            }
+        }
+        plain.split_whitespace().collect::<Vec<&str>>().join(" ")
+    }
+}
+
+fn flatten_text(component: &Value, out: &mut String) {
+    match component {
+        Value::String(text) => out.push_str(text),
+        Value::Array(components) => {
+            for component in components {
+                flatten_text(component, out);
+            }
+        }
+        Value::Object(object) => {
+            if let Some(text) = object.get("text") {
+                flatten_text(text, out);
+            }
+            if let Some(extra) = object.get("extra") {
+                flatten_text(extra, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value.cast_unsigned();
+    loop {
+        if value & !0x7F == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+}
+
+pub async fn read_varint<R: AsyncRead + Unpin>(reader: &mut R) -> Result<i32, ClientError> {
+    let mut value: u32 = 0;
+    for position in 0..5 {
+        let byte = reader.read_u8().await?;
+        value |= u32::from(byte & 0x7F) << (7 * position);
+        if byte & 0x80 == 0 {
+            return Ok(value.cast_signed());
+        }
+    }
+    Err(ClientError::ProtocolError("VarInt trop long.".to_owned()))
+}
+
+fn write_string(buf: &mut Vec<u8>, value: &str) -> Result<(), ClientError> {
+    write_varint(buf, i32::try_from(value.len())?);
+    buf.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+async fn write_packet(stream: &mut TcpStream, id: i32, data: &[u8]) -> Result<(), ClientError> {
+    let mut body = Vec::with_capacity(data.len() + 1);
+    write_varint(&mut body, id);
+    body.extend_from_slice(data);
+
+    let mut packet = Vec::with_capacity(body.len() + 5);
+    write_varint(&mut packet, i32::try_from(body.len())?);
+    packet.extend_from_slice(&body);
+
+    stream.write_all(&packet).await?;
+    Ok(())
+}
+
+async fn read_packet(stream: &mut TcpStream) -> Result<(i32, Vec<u8>), ClientError> {
+    let length = read_varint(stream).await?;
+    if !(1..=MAX_PACKET_LENGTH).contains(&length) {
+        return Err(ClientError::ProtocolError(format!(
+            "Taille de paquet invalide : {length}."
+        )));
+    }
+
+    let mut packet = vec![0; length as usize];
+    stream.read_exact(&mut packet).await?;
+
+    let mut cursor = packet.as_slice();
+    let id = read_varint(&mut cursor).await?;
+    Ok((id, cursor.to_vec()))
+}
+
+async fn exchange(host: &str, port: u16) -> Result<Status, ClientError> {
+    let mut stream = TcpStream::connect((host, port)).await?;
+
+    let mut handshake = Vec::new();
+    write_varint(&mut handshake, -1);
+    write_string(&mut handshake, host)?;
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut handshake, 1);
+    write_packet(&mut stream, 0x00, &handshake).await?;
+
+    write_packet(&mut stream, 0x00, &[]).await?;
+
+    let (id, data) = read_packet(&mut stream).await?;
+    if id != 0x00 {
+        return Err(ClientError::ProtocolError(format!(
+            "Paquet inattendu : {id:#04x}."
+        )));
+    }
+
+    let mut cursor = data.as_slice();
+    let length = read_varint(&mut cursor).await?;
+    let json = cursor
+        .get(..usize::try_from(length)?)
+        .ok_or_else(|| ClientError::ProtocolError("Statut tronqué.".to_owned()))?;
+    let mut status: Status = serde_json::from_slice(json)?;
+
+    let payload = chrono::Utc::now().timestamp_millis();
+    let sent = Instant::now();
+    write_packet(&mut stream, 0x01, &payload.to_be_bytes()).await?;
+
+    let (id, data) = read_packet(&mut stream).await?;
+    if id != 0x01 || data.as_slice() != payload.to_be_bytes() {
+        return Err(ClientError::ProtocolError("Pong invalide.".to_owned()));
+    }
+    status.latency = sent.elapsed();
+
+    Ok(status)
+}
+
+pub async fn status(host: &str, port: u16, timeout: Duration) -> Result<Status, ClientError> {
+    tokio::time::timeout(timeout, exchange(host, port))
+        .await
+        .map_err(|_| ClientError::ProtocolError(format!("{host}:{port} ne répond pas.")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[tokio::test]
+    async fn varint_round_trips() {
+        let cases: [(i32, &[u8]); 7] = [
+            (0, &[0x00]),
+            (1, &[0x01]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x01]),
+            (25_565, &[0xDD, 0xC7, 0x01]),
+            (i32::MAX, &[0xFF, 0xFF, 0xFF, 0xFF, 0x07]),
+            (-1, &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]),
+        ];
+
+        for (value, encoded) in cases {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, value);
+            assert_eq!(buf, encoded, "{value}");
+            assert_eq!(read_varint(&mut buf.as_slice()).await.unwrap(), value);
+        }
+
+        let mut buf = Vec::new();
+        write_varint(&mut buf, i32::MIN);
+        assert_eq!(buf.len(), 5);
+        assert_eq!(read_varint(&mut buf.as_slice()).await.unwrap(), i32::MIN);
+    }
+
+    #[tokio::test]
+    async fn varint_rejects_more_than_five_bytes() {
+        let mut bytes: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x80, 0x01];
+        assert!(read_varint(&mut bytes).await.is_err());
+    }
+
+    fn status_with(description: Value) -> Status {
+        Status {
+            version: Version {
+                name: "1.21".to_owned(),
+                protocol: 767,
+            },
+            players: Players { max: 20, online: 0 },
+            description,
+            latency: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn motd_flattens_text_components() {
+        assert_eq!(
+            status_with(json!("A §aMinecraft§r  Server")).motd(),
+            "A Minecraft Server"
+        );
+        assert_eq!(
+            status_with(json!({
+                "text": "Bien",
+                "extra": [{ "text": "venue", "bold": true }, " sur\n", { "extra": ["§6le serveur"] }],
+            }))
+            .motd(),
+            "Bienvenue sur le serveur"
+        );
+        assert_eq!(status_with(json!([{ "text": "a" }, "b", 3])).motd(), "ab");
+        assert_eq!(status_with(Value::Null).motd(), "");
+    }
+
+    // Plays the server side of a status exchange, answering the ping with `pong`.
+    async fn fake_server(pong: Option<(i32, Vec<u8>)>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let (id, handshake) = read_packet(&mut stream).await.unwrap();
+            assert_eq!(id, 0x00);
+            assert_eq!(*handshake.last().unwrap(), 1, "next state must be status");
+            assert_eq!(read_packet(&mut stream).await.unwrap(), (0x00, Vec::new()));
+
+            let mut response = Vec::new();
+            write_string(
+                &mut response,
+                r#"{"version":{"name":"1.21","protocol":767},"players":{"max":20,"online":3},"description":{"text":"Salut"}}"#,
+            )
+            .unwrap();
+            write_packet(&mut stream, 0x00, &response).await.unwrap();
+
+            let (id, payload) = read_packet(&mut stream).await.unwrap();
+            assert_eq!(id, 0x01);
+            let (id, payload) = pong.unwrap_or((id, payload));
+            write_packet(&mut stream, id, &payload).await.unwrap();
+        });
+
+        port
+    }
+
+    #[tokio::test]
+    async fn status_reads_the_server_answer() {
+        let port = fake_server(None).await;
+        let status = status("127.0.0.1", port, TIMEOUT).await.unwrap();
+
+        assert_eq!(status.version.name, "1.21");
+        assert_eq!(status.players.online, 3);
+        assert_eq!(status.players.max, 20);
+        assert_eq!(status.motd(), "Salut");
+    }
+
+    #[tokio::test]
+    async fn status_rejects_a_bad_pong() {
+        let port = fake_server(Some((0x01, 0_i64.to_be_bytes().to_vec()))).await;
+        assert!(status("127.0.0.1", port, TIMEOUT).await.is_err());
+
+        let port = fake_server(Some((0x02, Vec::new()))).await;
+        assert!(status("127.0.0.1", port, TIMEOUT).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn status_times_out_on_a_silent_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let error = status("127.0.0.1", port, Duration::from_millis(100))
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("ne répond pas"));
+        drop(listener);
+    }
+}
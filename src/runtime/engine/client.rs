@@ -0,0 +1,549 @@
+use crate::client::error::ClientError;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+const API_VERSION: &str = "v1.41";
+
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn error_for_status(self, method: &str, path: &str) -> Result<Self, ClientError> {
+        if self.status < 400 {
+            return Ok(self);
+        }
+
+        let message = serde_json::from_slice::<ErrorMessage>(&self.body).map_or_else(
+            |_| String::from_utf8_lossy(&self.body).trim().to_owned(),
+            |error| error.message,
+        );
+        Err(ClientError::RuntimeError(format!(
+            "{method} {path} ({}) : {message}",
+            self.status
+        )))
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ClientError> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorMessage {
+    message: String,
+}
+
+#[derive(Deserialize)]
+pub struct ContainerState {
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "Running")]
+    pub running: bool,
+    #[serde(rename = "ExitCode")]
+    pub exit_code: i64,
+    #[serde(rename = "Health")]
+    pub health: Option<ContainerHealth>,
+}
+
+#[derive(Deserialize)]
+pub struct ContainerHealth {
+    #[serde(rename = "Status")]
+    pub status: String,
+}
+
+#[derive(Deserialize)]
+pub struct ContainerInspect {
+    #[serde(rename = "State")]
+    pub state: ContainerState,
+}
+
+#[derive(Deserialize)]
+struct ContainerSummary {
+    #[serde(rename = "Id")]
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct IdResponse {
+    #[serde(rename = "Id")]
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct ExecInspect {
+    #[serde(rename = "ExitCode")]
+    exit_code: Option<i64>,
+}
+
+pub struct EngineClient {
+    socket: PathBuf,
+}
+
+impl EngineClient {
+    #[must_use]
+    pub const fn new(socket: PathBuf) -> Self {
+        Self { socket }
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Response, ClientError> {
+        let body = body
+            .map(serde_json::to_vec)
+            .transpose()?
+            .unwrap_or_default();
+
+        let mut stream = UnixStream::connect(&self.socket).await.map_err(|error| {
+            ClientError::RuntimeError(format!(
+                "Impossible de se connecter à {} : {error}",
+                self.socket.display()
+            ))
+        })?;
+
+        let head = format!(
+            "{method} /{API_VERSION}{path} HTTP/1.1\r\nHost: docker\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(&body).await?;
+
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).await?;
+
+        parse_response(&raw)
+    }
+
+    pub async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Response, ClientError> {
+        self.send(method, path, body)
+            .await?
+            .error_for_status(method, path)
+    }
+
+    pub async fn pull_image(&self, image: &str) -> Result<(), ClientError> {
+        let (name, tag) = image.rsplit_once(':').unwrap_or((image, "latest"));
+        self.request(
+            "POST",
+            &format!("/images/create?fromImage={name}&tag={tag}"),
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn create_container(
+        &self,
+        name: &str,
+        image: &str,
+        config: &Value,
+    ) -> Result<(), ClientError> {
+        let path = format!("/containers/create?name={name}");
+        let response = self.send("POST", &path, Some(config)).await?;
+        if response.status == 404 {
+            self.pull_image(image).await?;
+            self.request("POST", &path, Some(config)).await?;
+        } else {
+            response.error_for_status("POST", &path)?;
+        }
+        Ok(())
+    }
+
+    pub async fn start_container(&self, name: &str) -> Result<(), ClientError> {
+        self.request("POST", &format!("/containers/{name}/start"), None)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn stop_container(&self, name: &str, timeout: u64) -> Result<(), ClientError> {
+        self.request(
+            "POST",
+            &format!("/containers/{name}/stop?t={timeout}"),
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_container(&self, name: &str) -> Result<(), ClientError> {
+        self.request("DELETE", &format!("/containers/{name}?force=true"), None)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn inspect_container(
+        &self,
+        name: &str,
+    ) -> Result<Option<ContainerInspect>, ClientError> {
+        let path = format!("/containers/{name}/json");
+        let response = self.send("GET", &path, None).await?;
+        if response.status == 404 {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status("GET", &path)?.json()?))
+    }
+
+    // Returns the id of the first container, running or not, carrying every given label.
+    pub async fn find_container(&self, labels: &[String]) -> Result<Option<String>, ClientError> {
+        let filters = serde_json::json!({ "label": labels }).to_string();
+        let containers: Vec<ContainerSummary> = self
+            .request(
+                "GET",
+                &format!(
+                    "/containers/json?all=true&filters={}",
+                    encode_query(&filters)
+                ),
+                None,
+            )
+            .await?
+            .json()?;
+        Ok(containers.into_iter().next().map(|container| container.id))
+    }
+
+    pub async fn logs(&self, name: &str, query: &str) -> Result<String, ClientError> {
+        let response = self
+            .request(
+                "GET",
+                &format!("/containers/{name}/logs?stdout=1&stderr=1&{query}"),
+                None,
+            )
+            .await?;
+        Ok(String::from_utf8_lossy(&demux(&response.body)).into_owned())
+    }
+
+    pub async fn exec(&self, name: &str, command: &[&str]) -> Result<String, ClientError> {
+        let exec: IdResponse = self
+            .request(
+                "POST",
+                &format!("/containers/{name}/exec"),
+                Some(&serde_json::json!({
+                    "AttachStdout": true,
+                    "AttachStderr": true,
+                    "Cmd": command,
+                })),
+            )
+            .await?
+            .json()?;
+
+        let response = self
+            .request(
+                "POST",
+                &format!("/exec/{}/start", exec.id),
+                Some(&serde_json::json!({ "Detach": false, "Tty": false })),
+            )
+            .await?;
+        let output = String::from_utf8_lossy(&demux(&response.body)).into_owned();
+
+        let inspect: ExecInspect = self
+            .request("GET", &format!("/exec/{}/json", exec.id), None)
+            .await?
+            .json()?;
+        match inspect.exit_code {
+            Some(0) | None => Ok(output),
+            Some(code) => Err(ClientError::RuntimeError(format!(
+                "`{}` a échoué ({code}) : {}",
+                command.join(" "),
+                output.trim()
+            ))),
+        }
+    }
+}
+
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+fn parse_response(raw: &[u8]) -> Result<Response, ClientError> {
+    let invalid = || ClientError::RuntimeError("Réponse HTTP invalide du démon Docker.".to_owned());
+
+    let split = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(invalid)?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(invalid)?;
+
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(key, value)| {
+            key.eq_ignore_ascii_case("transfer-encoding") && value.trim() == "chunked"
+        })
+    });
+
+    Ok(Response {
+        status,
+        body: if chunked {
+            dechunk(body).ok_or_else(invalid)?
+        } else {
+            body.to_vec()
+        },
+    })
+}
+
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+// Without a TTY, Docker multiplexes stdout/stderr behind an 8-byte frame header.
+fn demux(body: &[u8]) -> Vec<u8> {
+    let is_multiplexed = body.len() >= 8 && body[0] <= 2 && body[1..4] == [0, 0, 0];
+    if !is_multiplexed {
+        return body.to_vec();
+    }
+
+    let mut out = Vec::with_capacity(body.len());
+    let mut rest = body;
+    while rest.len() >= 8 {
+        let size = u32::from_be_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let end = (8 + size).min(rest.len());
+        out.extend_from_slice(&rest[8..end]);
+        rest = &rest[end..];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UnixListener;
+
+    fn frame(stream: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![stream, 0, 0, 0];
+        out.extend_from_slice(&u32::try_from(payload.len()).unwrap().to_be_bytes());
+        out.extend_from_slice(payload);
+        out
+    }
+
+    #[test]
+    fn parses_a_plain_response() {
+        let response =
+            parse_response(b"HTTP/1.1 204 No Content\r\nContent-Length: 2\r\n\r\nok").unwrap();
+        assert_eq!(response.status, 204);
+        assert_eq!(response.body, b"ok");
+    }
+
+    #[test]
+    fn parses_a_chunked_response() {
+        let response = parse_response(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n7;ext=1\r\n, world\r\n0\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"hello, world");
+    }
+
+    #[test]
+    fn rejects_invalid_responses() {
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\n").is_err());
+        assert!(parse_response(b"garbage\r\n\r\n").is_err());
+        assert!(
+            parse_response(b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\nzz\r\n").is_err()
+        );
+    }
+
+    #[test]
+    fn dechunk_needs_complete_chunks() {
+        assert_eq!(dechunk(b"3\r\nabc\r\n0\r\n\r\n").unwrap(), b"abc");
+        assert_eq!(dechunk(b"0\r\n\r\n").unwrap(), b"");
+        assert!(dechunk(b"a\r\nshort\r\n").is_none());
+        assert!(dechunk(b"3\r\nabc").is_none());
+    }
+
+    #[test]
+    fn demux_strips_frame_headers() {
+        let mut body = frame(1, b"out\n");
+        body.extend(frame(2, b"err\n"));
+        assert_eq!(demux(&body), b"out\nerr\n");
+
+        assert_eq!(demux(b"plain tty output"), b"plain tty output");
+
+        let truncated = &frame(1, b"cut short")[..12];
+        assert_eq!(demux(truncated), b"cut ");
+    }
+
+    #[test]
+    fn encode_query_escapes_json() {
+        assert_eq!(
+            encode_query(r#"{"label":["a=1"]}"#),
+            "%7B%22label%22%3A%5B%22a%3D1%22%5D%7D"
+        );
+    }
+
+    #[test]
+    fn error_for_status_uses_the_engine_message() {
+        let error = Response {
+            status: 409,
+            body: br#"{"message":"name already in use"}"#.to_vec(),
+        }
+        .error_for_status("POST", "/containers/create")
+        .err()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "**docker erreur : POST /containers/create (409) : name already in use**"
+        );
+    }
+
+    // Answers each connection with the next canned response and returns the request lines it saw.
+    async fn fake_engine(
+        name: &str,
+        responses: Vec<&'static str>,
+    ) -> (EngineClient, tokio::task::JoinHandle<Vec<String>>) {
+        let socket =
+            std::env::temp_dir().join(format!("bot-mc-{name}-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        let server = tokio::spawn(async move {
+            let mut seen = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut raw = Vec::new();
+                let mut buffer = [0; 1024];
+                loop {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    raw.extend_from_slice(&buffer[..read]);
+                    let Some(split) = raw.windows(4).position(|window| window == b"\r\n\r\n")
+                    else {
+                        continue;
+                    };
+                    let head = String::from_utf8_lossy(&raw[..split]).into_owned();
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("Content-Length: "))
+                        .and_then(|length| length.parse::<usize>().ok())
+                        .unwrap_or_default();
+                    if raw.len() >= split + 4 + length {
+                        seen.push(head.lines().next().unwrap_or_default().to_owned());
+                        break;
+                    }
+                }
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            seen
+        });
+
+        (EngineClient::new(socket), server)
+    }
+
+    #[tokio::test]
+    async fn create_pulls_the_missing_image_then_retries() {
+        let (client, server) = fake_engine(
+            "create",
+            vec![
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 29\r\n\r\n{\"message\":\"No such image\"}\r\n",
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\n{}\r\n0\r\n\r\n",
+                "HTTP/1.1 201 Created\r\nContent-Length: 11\r\n\r\n{\"Id\":\"a\"}\n",
+            ],
+        )
+        .await;
+
+        client
+            .create_container(
+                "bot-mc-1",
+                "itzg/minecraft-server:java21",
+                &serde_json::json!({}),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.await.unwrap(),
+            [
+                "POST /v1.41/containers/create?name=bot-mc-1 HTTP/1.1",
+                "POST /v1.41/images/create?fromImage=itzg/minecraft-server&tag=java21 HTTP/1.1",
+                "POST /v1.41/containers/create?name=bot-mc-1 HTTP/1.1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn inspect_maps_404_to_none() {
+        let (client, server) = fake_engine(
+            "inspect",
+            vec![
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 2\r\n\r\n{}",
+                "HTTP/1.1 200 OK\r\nContent-Length: 72\r\n\r\n{\"State\":{\"Status\":\"exited\",\"Running\":false,\"ExitCode\":1,\"Health\":null}}",
+            ],
+        )
+        .await;
+
+        assert!(
+            client
+                .inspect_container("bot-mc-1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let state = client
+            .inspect_container("bot-mc-1")
+            .await
+            .unwrap()
+            .unwrap()
+            .state;
+        assert_eq!(state.status, "exited");
+        assert_eq!(state.exit_code, 1);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn find_container_filters_by_label() {
+        let (client, server) = fake_engine(
+            "find",
+            vec![
+                "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]",
+                "HTTP/1.1 200 OK\r\nContent-Length: 27\r\n\r\n[{\"Id\":\"abc\"},{\"Id\":\"def\"}]",
+            ],
+        )
+        .await;
+
+        let labels = ["com.docker.compose.project=1".to_owned()];
+        assert!(client.find_container(&labels).await.unwrap().is_none());
+        assert_eq!(
+            client.find_container(&labels).await.unwrap().as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            server.await.unwrap()[0],
+            "GET /v1.41/containers/json?all=true&filters=%7B%22label%22%3A%5B%22com.docker.compose.project%3D1%22%5D%7D HTTP/1.1"
+        );
+    }
+}
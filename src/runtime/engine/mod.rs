@@ -0,0 +1,314 @@
+use crate::client::error::ClientError;
+use crate::runtime::engine::client::{ContainerInspect, ContainerState, EngineClient};
+use crate::runtime::{ContainerRuntime, ContainerStatus};
+use crate::server::compose::{ServiceSpec, read_spec};
+use crate::util::{parse_duration, world_dir};
+use serde_json::{Value, json};
+use serenity::async_trait;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+pub mod client;
+
+const STOP_TIMEOUT_SECS: u64 = 60;
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+fn container_status(state: &ContainerState) -> ContainerStatus {
+    match (
+        state.status.as_str(),
+        state.health.as_ref().map(|health| health.status.as_str()),
+    ) {
+        ("running", Some("starting")) | ("restarting", _) => ContainerStatus::Starting,
+        ("running", Some("unhealthy")) => ContainerStatus::Unhealthy,
+        ("running", _) => ContainerStatus::Running,
+        _ => ContainerStatus::Stopped,
+    }
+}
+
+pub struct DockerEngine {
+    client: EngineClient,
+}
+
+impl DockerEngine {
+    #[must_use]
+    pub const fn new(socket: PathBuf) -> Self {
+        Self {
+            client: EngineClient::new(socket),
+        }
+    }
+
+    fn container_name(id: i64) -> String {
+        format!("bot-mc-{id}")
+    }
+
+    // Servers started before the Engine backend were run by `docker compose` from `worlds/{id}`,
+    // whose project name is the directory name.
+    fn legacy_labels(id: i64) -> Vec<String> {
+        vec![
+            format!("com.docker.compose.project={id}"),
+            "com.docker.compose.service=mc".to_owned(),
+        ]
+    }
+
+    async fn locate(&self, id: i64) -> Result<Option<(String, ContainerInspect)>, ClientError> {
+        let name = Self::container_name(id);
+        if let Some(inspect) = self.client.inspect_container(&name).await? {
+            return Ok(Some((name, inspect)));
+        }
+
+        let Some(legacy) = self.client.find_container(&Self::legacy_labels(id)).await? else {
+            return Ok(None);
+        };
+        Ok(self
+            .client
+            .inspect_container(&legacy)
+            .await?
+            .map(|inspect| (legacy, inspect)))
+    }
+
+    async fn name(&self, id: i64) -> Result<String, ClientError> {
+        Ok(self
+            .locate(id)
+            .await?
+            .map_or_else(|| Self::container_name(id), |(name, _)| name))
+    }
+
+    fn container_config(id: i64, spec: &ServiceSpec) -> Result<Value, ClientError> {
+        let mut exposed_ports = serde_json::Map::new();
+        let mut port_bindings = serde_json::Map::new();
+        for port in &spec.ports {
+            let (published, container) = port.rsplit_once(':').unwrap_or((port, port));
+            let (host_ip, host_port) = published.rsplit_once(':').unwrap_or(("", published));
+            let container = format!("{container}/tcp");
+            exposed_ports.insert(container.clone(), json!({}));
+            port_bindings.insert(
+                container,
+                json!([{ "HostIp": host_ip, "HostPort": host_port }]),
+            );
+        }
+
+        let binds = spec
+            .volumes
+            .iter()
+            .map(|volume| {
+                let (source, target) = volume
+                    .split_once(':')
+                    .ok_or_else(|| ClientError::Other(format!("Volume invalide : {volume}")))?;
+                let source =
+                    std::path::absolute(world_dir(id).join(source.trim_start_matches("./")))?;
+                Ok(format!("{}:{target}", source.display()))
+            })
+            .collect::<Result<Vec<String>, ClientError>>()?;
+
+        let mut config = json!({
+            "Image": spec.image,
+            "Tty": spec.tty,
+            "OpenStdin": spec.stdin_open,
+            "Env": spec
+                .environment
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<String>>(),
+            "ExposedPorts": exposed_ports,
+            "Labels": { "bot-mc.server": id.to_string() },
+            "HostConfig": {
+                "PortBindings": port_bindings,
+                "Binds": binds,
+            },
+        });
+
+        if let Some(healthcheck) = &spec.healthcheck {
+            config["Healthcheck"] = json!({
+                "Test": ["CMD-SHELL", healthcheck.test],
+                "StartPeriod": parse_duration(&healthcheck.start_period)?.as_nanos(),
+                "Interval": parse_duration(&healthcheck.interval)?.as_nanos(),
+                "Retries": healthcheck.retries.parse::<u64>().map_err(|error| {
+                    ClientError::Other(format!("Healthcheck retries invalide : {error}"))
+                })?,
+            });
+        }
+
+        Ok(config)
+    }
+
+    fn wait_timeout(spec: &ServiceSpec) -> Result<Duration, ClientError> {
+        let Some(healthcheck) = &spec.healthcheck else {
+            return Ok(Duration::ZERO);
+        };
+        let retries = healthcheck.retries.parse::<u32>().unwrap_or_default();
+        Ok(parse_duration(&healthcheck.start_period)?
+            + parse_duration(&healthcheck.interval)? * retries.saturating_add(1))
+    }
+
+    async fn wait_healthy(&self, name: &str, timeout: Duration) -> Result<(), ClientError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let inspect = self.client.inspect_container(name).await?.ok_or_else(|| {
+                ClientError::RuntimeError(format!("Le conteneur {name} a disparu."))
+            })?;
+            let state = inspect.state;
+
+            if !state.running {
+                return Err(ClientError::RuntimeError(format!(
+                    "Le conteneur {name} s'est arrêté (code {}).",
+                    state.exit_code
+                )));
+            }
+            match state.health.as_ref().map(|health| health.status.as_str()) {
+                Some("healthy") | None => return Ok(()),
+                Some("unhealthy") => {
+                    return Err(ClientError::RuntimeError(format!(
+                        "Le healthcheck du conteneur {name} a échoué."
+                    )));
+                }
+                _ => {}
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(ClientError::RuntimeError(format!(
+                    "Le conteneur {name} n'est pas devenu sain en {} secondes.",
+                    timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[async_trait]
+impl ContainerRuntime for DockerEngine {
+    async fn up(&self, id: i64) -> Result<(), ClientError> {
+        let spec = read_spec(id).await?;
+        let config = Self::container_config(id, &spec)?;
+        let name = Self::container_name(id);
+
+        // A leftover container (crash, manual stop, old compose backend) would keep an outdated
+        // configuration.
+        if let Some((leftover, _)) = self.locate(id).await? {
+            self.client.remove_container(&leftover).await?;
+        }
+
+        self.client
+            .create_container(&name, &spec.image, &config)
+            .await?;
+
+        self.client.start_container(&name).await?;
+        self.wait_healthy(&name, Self::wait_timeout(&spec)?).await
+    }
+
+    async fn down(&self, id: i64) -> Result<(), ClientError> {
+        let Some((name, inspect)) = self.locate(id).await? else {
+            return Ok(());
+        };
+
+        if inspect.state.running {
+            self.client.stop_container(&name, STOP_TIMEOUT_SECS).await?;
+        }
+        self.client.remove_container(&name).await
+    }
+
+    async fn status(&self, id: i64) -> Result<ContainerStatus, ClientError> {
+        let Some((_, inspect)) = self.locate(id).await? else {
+            return Ok(ContainerStatus::Missing);
+        };
+
+        Ok(container_status(&inspect.state))
+    }
+
+    async fn logs(&self, id: i64, lines: u32) -> Result<String, ClientError> {
+        self.client
+            .logs(&self.name(id).await?, &format!("tail={lines}"))
+            .await
+    }
+
+    async fn logs_between(
+        &self,
+        id: i64,
+        since: SystemTime,
+        until: SystemTime,
+    ) -> Result<String, ClientError> {
+        self.client
+            .logs(
+                &self.name(id).await?,
+                &format!(
+                    "since={}&until={}",
+                    unix_timestamp(since),
+                    unix_timestamp(until)
+                ),
+            )
+            .await
+    }
+
+    async fn exec(&self, id: i64, command: &[&str]) -> Result<String, ClientError> {
+        self.client.exec(&self.name(id).await?, command).await
+    }
+}
+
+fn unix_timestamp(time: SystemTime) -> String {
+    let elapsed = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{}.{:09}", elapsed.as_secs(), elapsed.subsec_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::engine::client::ContainerHealth;
+
+    fn state(status: &str, health: Option<&str>) -> ContainerState {
+        ContainerState {
+            status: status.to_owned(),
+            running: status == "running",
+            exit_code: 0,
+            health: health.map(|status| ContainerHealth {
+                status: status.to_owned(),
+            }),
+        }
+    }
+
+    #[test]
+    fn container_config_binds_host_addresses() {
+        let spec = ServiceSpec {
+            image: "itzg/minecraft-server".to_owned(),
+            tty: true,
+            stdin_open: true,
+            ports: vec!["10000:25565".to_owned(), "127.0.0.1:10001:25575".to_owned()],
+            environment: std::collections::BTreeMap::new(),
+            volumes: Vec::new(),
+            healthcheck: None,
+        };
+
+        let config = DockerEngine::container_config(1, &spec).unwrap();
+        let bindings = &config["HostConfig"]["PortBindings"];
+        assert_eq!(
+            bindings["25565/tcp"],
+            json!([{ "HostIp": "", "HostPort": "10000" }])
+        );
+        assert_eq!(
+            bindings["25575/tcp"],
+            json!([{ "HostIp": "127.0.0.1", "HostPort": "10001" }])
+        );
+    }
+
+    #[test]
+    fn container_status_mapping() {
+        let cases = [
+            ("running", None, ContainerStatus::Running),
+            ("running", Some("healthy"), ContainerStatus::Running),
+            ("running", Some("starting"), ContainerStatus::Starting),
+            ("running", Some("unhealthy"), ContainerStatus::Unhealthy),
+            ("restarting", None, ContainerStatus::Starting),
+            ("created", None, ContainerStatus::Stopped),
+            ("exited", Some("unhealthy"), ContainerStatus::Stopped),
+            ("dead", None, ContainerStatus::Stopped),
+        ];
+
+        for (status, health, expected) in cases {
+            assert_eq!(
+                container_status(&state(status, health)),
+                expected,
+                "{status} {health:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::client::error::ClientError;
+use crate::runtime::{ContainerRuntime, ContainerStatus};
+use serenity::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+#[derive(Default)]
+struct Container {
+    status: Option<ContainerStatus>,
+    logs: Vec<(SystemTime, String)>,
+}
+
+#[derive(Default)]
+pub struct InMemoryRuntime {
+    containers: Mutex<HashMap<i64, Container>>,
+}
+
+impl InMemoryRuntime {
+    fn with_container<T>(&self, id: i64, f: impl FnOnce(&mut Container) -> T) -> T {
+        f(self.containers.lock().unwrap().entry(id).or_default())
+    }
+
+    pub fn set_status(&self, id: i64, status: ContainerStatus) {
+        self.with_container(id, |container| container.status = Some(status));
+    }
+
+    pub fn push_log(&self, id: i64, line: impl Into<String>) {
+        self.with_container(id, |container| {
+            container.logs.push((SystemTime::now(), line.into()));
+        });
+    }
+}
+
+#[async_trait]
+impl ContainerRuntime for InMemoryRuntime {
+    async fn up(&self, id: i64) -> Result<(), ClientError> {
+        self.with_container(id, |container| {
+            container.status = Some(ContainerStatus::Running);
+            container
+                .logs
+                .push((SystemTime::now(), "Container started".to_owned()));
+        });
+        Ok(())
+    }
+
+    async fn down(&self, id: i64) -> Result<(), ClientError> {
+        self.with_container(id, |container| {
+            container.status = None;
+            container
+                .logs
+                .push((SystemTime::now(), "Container removed".to_owned()));
+        });
+        Ok(())
+    }
+
+    async fn status(&self, id: i64) -> Result<ContainerStatus, ClientError> {
+        Ok(self
+            .with_container(id, |container| container.status)
+            .unwrap_or(ContainerStatus::Missing))
+    }
+
+    async fn logs(&self, id: i64, lines: u32) -> Result<String, ClientError> {
+        Ok(self.with_container(id, |container| {
+            let logs = &container.logs;
+            logs[logs.len().saturating_sub(lines as usize)..]
+                .iter()
+                .map(|(_, line)| line.as_str())
+                .collect::<Vec<&str>>()
+                .join("\n")
+        }))
+    }
+
+    async fn logs_between(
+        &self,
+        id: i64,
+        since: SystemTime,
+        until: SystemTime,
+    ) -> Result<String, ClientError> {
+        Ok(self.with_container(id, |container| {
+            container
+                .logs
+                .iter()
+                .filter(|(time, _)| (since..until).contains(time))
+                .map(|(_, line)| line.as_str())
+                .collect::<Vec<&str>>()
+                .join("\n")
+        }))
+    }
+
+    async fn exec(&self, id: i64, command: &[&str]) -> Result<String, ClientError> {
+        self.with_container(id, |container| {
+            if !container.status.is_some_and(ContainerStatus::is_up) {
+                return Err(ClientError::RuntimeError(format!(
+                    "Le conteneur {id} n'est pas lancé."
+                )));
+            }
+            container
+                .logs
+                .push((SystemTime::now(), format!("exec: {}", command.join(" "))));
+            Ok(String::new())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn up_and_down_drive_the_status() {
+        let runtime = InMemoryRuntime::default();
+
+        assert_eq!(runtime.status(1).await.unwrap(), ContainerStatus::Missing);
+        runtime.up(1).await.unwrap();
+        assert_eq!(runtime.status(1).await.unwrap(), ContainerStatus::Running);
+        assert_eq!(runtime.status(2).await.unwrap(), ContainerStatus::Missing);
+        runtime.down(1).await.unwrap();
+        assert_eq!(runtime.status(1).await.unwrap(), ContainerStatus::Missing);
+    }
+
+    #[tokio::test]
+    async fn exec_needs_a_running_container() {
+        let runtime = InMemoryRuntime::default();
+
+        assert!(runtime.exec(1, &["list"]).await.is_err());
+        runtime.set_status(1, ContainerStatus::Unhealthy);
+        runtime.exec(1, &["list"]).await.unwrap();
+        assert_eq!(runtime.logs(1, 1).await.unwrap(), "exec: list");
+    }
+
+    #[tokio::test]
+    async fn logs_keep_the_last_lines() {
+        let runtime = InMemoryRuntime::default();
+        for line in ["a", "b", "c"] {
+            runtime.push_log(1, line);
+        }
+
+        assert_eq!(runtime.logs(1, 2).await.unwrap(), "b\nc");
+        assert_eq!(runtime.logs(1, 10).await.unwrap(), "a\nb\nc");
+        let all = runtime
+            .logs_between(1, SystemTime::UNIX_EPOCH, SystemTime::now())
+            .await
+            .unwrap();
+        assert_eq!(all, "a\nb\nc");
+    }
+}
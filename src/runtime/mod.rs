@@ -0,0 +1,56 @@
+use crate::client::error::ClientError;
+use serenity::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+pub mod engine;
+pub mod memory;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerStatus {
+    Missing,
+    Stopped,
+    Starting,
+    Running,
+    Unhealthy,
+}
+
+impl ContainerStatus {
+    #[must_use]
+    pub const fn is_up(self) -> bool {
+        matches!(self, Self::Starting | Self::Running | Self::Unhealthy)
+    }
+}
+
+#[async_trait]
+pub trait ContainerRuntime: Send + Sync {
+    async fn up(&self, id: i64) -> Result<(), ClientError>;
+
+    async fn down(&self, id: i64) -> Result<(), ClientError>;
+
+    async fn status(&self, id: i64) -> Result<ContainerStatus, ClientError>;
+
+    async fn logs(&self, id: i64, lines: u32) -> Result<String, ClientError>;
+
+    async fn logs_between(
+        &self,
+        id: i64,
+        since: SystemTime,
+        until: SystemTime,
+    ) -> Result<String, ClientError>;
+
+    async fn exec(&self, id: i64, command: &[&str]) -> Result<String, ClientError>;
+}
+
+pub fn from_env() -> Result<Arc<dyn ContainerRuntime>, ClientError> {
+    match std::env::var("CONTAINER_RUNTIME").as_deref() {
+        Ok("engine") | Err(_) => Ok(Arc::new(engine::DockerEngine::new(PathBuf::from(
+            std::env::var("DOCKER_SOCKET").unwrap_or_else(|_| "/var/run/docker.sock".to_owned()),
+        )))),
+        Ok("memory") => Ok(Arc::new(memory::InMemoryRuntime::default())),
+        Ok(other) => Err(ClientError::Other(format!(
+            "Unknown `CONTAINER_RUNTIME` : {other}."
+        ))),
+    }
+}
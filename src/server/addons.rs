@@ -0,0 +1,151 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::server_addons::dsl as addons_dsl;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::software::ServerType;
+use crate::util::world_dir;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddonKind {
+    Mod,
+    Plugin,
+}
+
+impl AddonKind {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Mod => "mod",
+            Self::Plugin => "plugin",
+        }
+    }
+
+    #[must_use]
+    pub const fn directory(self) -> &'static str {
+        match self {
+            Self::Mod => "mods",
+            Self::Plugin => "plugins",
+        }
+    }
+
+    #[must_use]
+    pub const fn supported_by(self, server_type: ServerType) -> bool {
+        match self {
+            Self::Mod => matches!(
+                server_type,
+                ServerType::Fabric | ServerType::Forge | ServerType::NeoForge
+            ),
+            Self::Plugin => matches!(
+                server_type,
+                ServerType::Paper | ServerType::Purpur | ServerType::Spigot
+            ),
+        }
+    }
+
+    // Variable of the itzg/minecraft-server image downloading this kind of jar from URLs.
+    const fn url_env(self) -> &'static str {
+        match self {
+            Self::Mod => "MODS",
+            Self::Plugin => "PLUGINS",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddonSource {
+    Modrinth,
+    Url,
+    File,
+}
+
+impl AddonSource {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Modrinth => "modrinth",
+            Self::Url => "url",
+            Self::File => "file",
+        }
+    }
+
+    #[must_use]
+    pub fn label(source: &str) -> &'static str {
+        match source {
+            "modrinth" => "Modrinth",
+            "url" => "URL",
+            _ => "fichier",
+        }
+    }
+}
+
+#[must_use]
+pub fn jar_path(id: i64, kind: AddonKind, file_name: &str) -> PathBuf {
+    world_dir(id)
+        .join("data")
+        .join(kind.directory())
+        .join(file_name)
+}
+
+pub async fn apply(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    environment: &mut BTreeMap<String, String>,
+) -> Result<(), ClientError> {
+    let server_type: ServerType = servers_dsl::servers
+        .select(servers_dsl::server_type)
+        .filter(servers_dsl::id.eq(id))
+        .get_result(conn)
+        .await?;
+
+    let addons: Vec<(String, String, String)> = addons_dsl::server_addons
+        .select((addons_dsl::kind, addons_dsl::source, addons_dsl::value))
+        .filter(addons_dsl::server_id.eq(id))
+        .order_by(addons_dsl::id)
+        .load(conn)
+        .await?;
+
+    let values = |kind: Option<AddonKind>, source: AddonSource| -> String {
+        addons
+            .iter()
+            .filter(|(addon_kind, addon_source, _)| {
+                kind.is_none_or(|kind| addon_kind == kind.as_str())
+                    && addon_source == source.as_str()
+            })
+            .map(|(_, _, value)| value.as_str())
+            .collect::<Vec<&str>>()
+            .join(",")
+    };
+
+    let mut set = |key: &str, value: String| {
+        if value.is_empty() {
+            environment.remove(key);
+        } else {
+            environment.insert(key.to_owned(), value);
+        }
+    };
+
+    set("MODRINTH_PROJECTS", values(None, AddonSource::Modrinth));
+    for kind in [AddonKind::Mod, AddonKind::Plugin] {
+        set(kind.url_env(), values(Some(kind), AddonSource::Url));
+    }
+
+    // The image only downloads jars, so the ones removed from the lists would stay installed.
+    // Uploaded jars are not downloaded again and must survive the cleanup. Servers without
+    // addons keep whatever was put in their directories by hand.
+    let takes_addons = [AddonKind::Mod, AddonKind::Plugin]
+        .into_iter()
+        .any(|kind| kind.supported_by(server_type));
+    if takes_addons && !addons.is_empty() {
+        set("REMOVE_OLD_MODS", "TRUE".to_owned());
+        set("REMOVE_OLD_MODS_EXCLUDE", values(None, AddonSource::File));
+    } else {
+        set("REMOVE_OLD_MODS", String::new());
+        set("REMOVE_OLD_MODS_EXCLUDE", String::new());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,538 @@
+use crate::client::error::ClientError;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use tar::EntryType;
+use zip::ZipArchive;
+
+const MAX_WORLD_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    #[must_use]
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let path = Path::new(&name);
+        match path.extension()?.to_str()? {
+            "zip" => Some(Self::Zip),
+            "tgz" => Some(Self::TarGz),
+            "gz" if Path::new(path.file_stem()?)
+                .extension()
+                .is_some_and(|extension| extension == "tar") =>
+            {
+                Some(Self::TarGz)
+            }
+            _ => None,
+        }
+    }
+}
+
+pub struct WorldArchive {
+    format: ArchiveFormat,
+    path: PathBuf,
+    root: PathBuf,
+}
+
+impl WorldArchive {
+    // Checks every entry before anything is extracted and locates the folder holding `level.dat`.
+    pub fn inspect(format: ArchiveFormat, path: PathBuf) -> Result<Self, ClientError> {
+        let mut total: u64 = 0;
+        let mut root: Option<PathBuf> = None;
+
+        walk(format, fs::File::open(&path)?, |path, is_dir, size, _| {
+            total = total.saturating_add(size);
+            if total > MAX_WORLD_SIZE {
+                return Err(too_large());
+            }
+
+            if !is_dir
+                && path.file_name().is_some_and(|name| name == "level.dat")
+                && let Some(parent) = path.parent()
+                && root
+                    .as_ref()
+                    .is_none_or(|root| parent.components().count() < root.components().count())
+            {
+                root = Some(parent.to_path_buf());
+            }
+            Ok(())
+        })?;
+
+        let root = root.ok_or(ClientError::OtherStatic(
+            "L'archive ne contient pas de level.dat.",
+        ))?;
+
+        Ok(Self { format, path, root })
+    }
+
+    // Extracts the world folder into `dest`, dropping whatever sits outside of it in the archive.
+    pub fn extract(&self, dest: &Path) -> Result<(), ClientError> {
+        extract_into(
+            self.format,
+            fs::File::open(&self.path)?,
+            &self.root,
+            dest,
+            MAX_WORLD_SIZE,
+        )
+    }
+}
+
+// Unpacks a tar.gz produced by `pack_worlds` into `dest`.
+pub fn unpack_worlds(archive: &Path, dest: &Path) -> Result<(), ClientError> {
+    extract_into(
+        ArchiveFormat::TarGz,
+        fs::File::open(archive)?,
+        Path::new(""),
+        dest,
+        u64::MAX,
+    )
+}
+
+// Swaps the `world*` folders of `data_dir` for the ones of `archive`, putting everything back on failure.
+pub fn replace_worlds(archive: &Path, data_dir: &Path) -> Result<(), ClientError> {
+    let staging = data_dir.join(".restore");
+    let previous = data_dir.join(".previous");
+    for dir in [&staging, &previous] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+    }
+
+    if let Err(error) = unpack_worlds(archive, &staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(error);
+    }
+    let restored = world_dirs(&staging);
+    if restored.is_empty() {
+        fs::remove_dir_all(&staging)?;
+        return Err(ClientError::OtherStatic(
+            "La sauvegarde ne contient aucun monde.",
+        ));
+    }
+    fs::create_dir(&previous)?;
+
+    let moves: Vec<(PathBuf, PathBuf)> = world_dirs(data_dir)
+        .into_iter()
+        .map(|world| (world, previous.as_path()))
+        .chain(restored.into_iter().map(|world| (world, data_dir)))
+        .filter_map(|(world, to)| {
+            let target = to.join(world.file_name()?);
+            Some((world, target))
+        })
+        .collect();
+
+    for (done, (from, to)) in moves.iter().enumerate() {
+        if let Err(error) = fs::rename(from, to) {
+            for (from, to) in moves[..done].iter().rev() {
+                if let Err(error) = fs::rename(to, from) {
+                    log::error!("Unable to move {} back: {error}", to.display());
+                }
+            }
+            let _ = fs::remove_dir_all(&staging);
+            let _ = fs::remove_dir_all(&previous);
+            return Err(error.into());
+        }
+    }
+
+    fs::remove_dir_all(&previous)?;
+    fs::remove_dir_all(&staging)?;
+    Ok(())
+}
+
+#[must_use]
+pub fn world_dirs(data_dir: &Path) -> Vec<PathBuf> {
+    let mut worlds: Vec<PathBuf> = fs::read_dir(data_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("world"))
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .map(|entry| entry.path())
+        .collect();
+    worlds.sort();
+    worlds
+}
+
+#[must_use]
+pub fn dir_size(path: &Path) -> u64 {
+    fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map_or(0, |metadata| metadata.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+// Packs every `world*` folder of a server data directory into a tar.gz at `dest`.
+pub fn pack_worlds(data_dir: &Path, dest: &Path) -> Result<u64, ClientError> {
+    let worlds = world_dirs(data_dir);
+    if worlds.is_empty() {
+        return Err(ClientError::OtherStatic(
+            "Ce serveur n'a pas encore de monde.",
+        ));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let partial = dest.with_extension("part");
+
+    if let Err(error) = write_worlds(&worlds, &partial) {
+        let _ = fs::remove_file(&partial);
+        return Err(error.into());
+    }
+
+    fs::rename(&partial, dest)?;
+    Ok(fs::metadata(dest)?.len())
+}
+
+fn write_worlds(worlds: &[PathBuf], dest: &Path) -> io::Result<()> {
+    let mut builder = tar::Builder::new(GzEncoder::new(
+        fs::File::create(dest)?,
+        Compression::default(),
+    ));
+    for world in worlds {
+        if let Some(name) = world.file_name() {
+            builder.append_dir_all(name, world)?;
+        }
+    }
+    builder.into_inner()?.finish()?.sync_all()
+}
+
+fn extract_into<R: Read + Seek>(
+    format: ArchiveFormat,
+    reader: R,
+    root: &Path,
+    dest: &Path,
+    max_size: u64,
+) -> Result<(), ClientError> {
+    let mut written: u64 = 0;
+
+    walk(format, reader, |path, is_dir, _, entry| {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return Ok(());
+        };
+        if relative.as_os_str().is_empty() {
+            return Ok(());
+        }
+
+        let target = dest.join(relative);
+        if is_dir {
+            fs::create_dir_all(target)?;
+            return Ok(());
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Sizes from the headers are not trusted, the copy itself is capped.
+        let mut file = fs::File::create(target)?;
+        let remaining = (max_size - written).saturating_add(1);
+        written += io::copy(&mut entry.take(remaining), &mut file)?;
+        if written > max_size {
+            return Err(too_large());
+        }
+        Ok(())
+    })
+}
+
+fn walk<R: Read + Seek>(
+    format: ArchiveFormat,
+    reader: R,
+    mut visit: impl FnMut(&Path, bool, u64, &mut dyn Read) -> Result<(), ClientError>,
+) -> Result<(), ClientError> {
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive = ZipArchive::new(reader).map_err(invalid)?;
+            for index in 0..archive.len() {
+                let mut file = archive.by_index(index).map_err(invalid)?;
+                let name = file.name().map_err(invalid)?.into_owned();
+                if file.is_symlink() {
+                    return Err(forbidden_entry(&name));
+                }
+                let path = file
+                    .enclosed_name()
+                    .and_then(|path| safe_path(&path))
+                    .ok_or_else(|| forbidden_entry(&name))?;
+                let (is_dir, size) = (file.is_dir(), file.size());
+                visit(&path, is_dir, size, &mut file)?;
+            }
+        }
+        ArchiveFormat::TarGz => {
+            let mut archive = tar::Archive::new(GzDecoder::new(reader));
+            for entry in archive.entries().map_err(invalid)? {
+                let mut entry = entry.map_err(invalid)?;
+                let raw_path = entry.path().map_err(invalid)?.into_owned();
+                let entry_type = entry.header().entry_type();
+
+                let is_dir = match entry_type {
+                    EntryType::Directory => true,
+                    EntryType::Regular | EntryType::Continuous => false,
+                    EntryType::Symlink | EntryType::Link => {
+                        return Err(forbidden_entry(&raw_path.display().to_string()));
+                    }
+                    _ => continue,
+                };
+                let path = safe_path(&raw_path)
+                    .ok_or_else(|| forbidden_entry(&raw_path.display().to_string()))?;
+                let size = entry.size();
+                visit(&path, is_dir, size, &mut entry)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn safe_path(path: &Path) -> Option<PathBuf> {
+    let mut safe = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => safe.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(safe)
+}
+
+fn invalid(error: impl Display) -> ClientError {
+    ClientError::Other(format!("Archive invalide : {error}"))
+}
+
+fn forbidden_entry(name: &str) -> ClientError {
+    ClientError::Other(format!(
+        "L'archive contient une entrée interdite : ``{name}``"
+    ))
+}
+
+fn too_large() -> ClientError {
+    ClientError::Other(format!(
+        "Le monde dépasse {} Go une fois décompressé.",
+        MAX_WORLD_SIZE / 1024 / 1024 / 1024
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("archive-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Headers are filled by hand, the builder refuses the names these tests need.
+    fn tar_gz(entries: &[(&str, EntryType, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, entry_type, data) in entries {
+            let mut header = tar::Header::new_old();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.as_old_mut().linkname[..6].copy_from_slice(b"target");
+            header.set_entry_type(*entry_type);
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn check(format: ArchiveFormat, archive: Vec<u8>) -> Result<(), ClientError> {
+        walk(format, Cursor::new(archive), |_, _, _, _| Ok(()))
+    }
+
+    #[test]
+    fn formats() {
+        for (name, format) in [
+            ("world.tar.gz", Some(ArchiveFormat::TarGz)),
+            ("World.TGZ", Some(ArchiveFormat::TarGz)),
+            ("world.zip", Some(ArchiveFormat::Zip)),
+            ("world.gz", None),
+            ("world.tar", None),
+            ("world", None),
+        ] {
+            assert_eq!(ArchiveFormat::from_file_name(name), format, "{name}");
+        }
+    }
+
+    #[test]
+    fn rejects_escaping_entries() {
+        for name in ["../level.dat", "/etc/level.dat", "world/../../level.dat"] {
+            let tar = tar_gz(&[(name, EntryType::Regular, b"")]);
+            assert!(check(ArchiveFormat::TarGz, tar).is_err(), "{name}");
+        }
+        for name in ["../level.dat", "world/../../level.dat"] {
+            let zip = zip(&[(name, b"")]);
+            assert!(check(ArchiveFormat::Zip, zip).is_err(), "{name}");
+        }
+
+        // The writer strips leading slashes, the name is patched in both headers. Absolute zip
+        // entries are read as relative ones and so stay inside the destination.
+        let mut zip = zip(&[("_etc/level.dat", b"")]);
+        for index in 0..zip.len() - 4 {
+            if &zip[index..index + 4] == b"_etc" {
+                zip[index] = b'/';
+            }
+        }
+        let mut paths = Vec::new();
+        walk(ArchiveFormat::Zip, Cursor::new(zip), |path, _, _, _| {
+            paths.push(path.to_path_buf());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(paths, [Path::new("etc/level.dat")]);
+
+        let tar = tar_gz(&[("./world/level.dat", EntryType::Regular, b"")]);
+        assert!(check(ArchiveFormat::TarGz, tar).is_ok());
+    }
+
+    #[test]
+    fn rejects_links() {
+        for entry_type in [EntryType::Symlink, EntryType::Link] {
+            let tar = tar_gz(&[("world/level.dat", entry_type, b"")]);
+            assert!(check(ArchiveFormat::TarGz, tar).is_err());
+        }
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .add_symlink(
+                "world/level.dat",
+                "/etc/passwd",
+                SimpleFileOptions::default(),
+            )
+            .unwrap();
+        let zip = writer.finish().unwrap().into_inner();
+        assert!(check(ArchiveFormat::Zip, zip).is_err());
+    }
+
+    // The uncompressed size of the only entry is rewritten to 1 in both of its headers.
+    #[test]
+    fn caps_under_reported_sizes() {
+        let mut zip = zip(&[("world/level.dat", &[0; 1000])]);
+        let local = 0;
+        let central = zip
+            .windows(4)
+            .position(|window| window == b"PK\x01\x02")
+            .unwrap();
+        zip[local + 22..local + 26].copy_from_slice(&1_u32.to_le_bytes());
+        zip[central + 24..central + 28].copy_from_slice(&1_u32.to_le_bytes());
+
+        let mut reported = 0;
+        walk(
+            ArchiveFormat::Zip,
+            Cursor::new(zip.clone()),
+            |_, _, size, _| {
+                reported += size;
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(reported, 1);
+
+        let dest = temp_dir("under-reported");
+        let result = extract_into(
+            ArchiveFormat::Zip,
+            Cursor::new(zip),
+            Path::new(""),
+            &dest,
+            100,
+        );
+        assert!(result.is_err());
+        assert!(fs::metadata(dest.join("world/level.dat")).unwrap().len() <= 101);
+        fs::remove_dir_all(dest).unwrap();
+    }
+
+    #[test]
+    fn extracts_the_shallowest_world() {
+        let dir = temp_dir("shallowest");
+        let path = dir.join("world.tar.gz");
+        fs::write(
+            &path,
+            tar_gz(&[
+                ("backup/old/world/level.dat", EntryType::Regular, b"old"),
+                ("backup/world/level.dat", EntryType::Regular, b"new"),
+                (
+                    "backup/world/region/r.0.0.mca",
+                    EntryType::Regular,
+                    b"region",
+                ),
+                ("backup/readme.txt", EntryType::Regular, b"readme"),
+            ]),
+        )
+        .unwrap();
+
+        let archive = WorldArchive::inspect(ArchiveFormat::TarGz, path).unwrap();
+        assert_eq!(archive.root, Path::new("backup/world"));
+
+        let dest = dir.join("world");
+        archive.extract(&dest).unwrap();
+        assert_eq!(fs::read(dest.join("level.dat")).unwrap(), b"new");
+        assert!(dest.join("region/r.0.0.mca").exists());
+        assert!(!dest.join("readme.txt").exists());
+        assert!(!dir.join("readme.txt").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn replace_worlds_rolls_back() {
+        let dir = temp_dir("rollback");
+        let source = dir.join("source");
+        for world in ["world", "world_nether"] {
+            fs::create_dir_all(source.join(world)).unwrap();
+            fs::write(source.join(world).join("level.dat"), "new").unwrap();
+        }
+        let archive = dir.join("backup.tar.gz");
+        pack_worlds(&source, &archive).unwrap();
+
+        // A file in the way of the second world makes its rename fail after the first one moved.
+        let data_dir = dir.join("data");
+        fs::create_dir_all(data_dir.join("world")).unwrap();
+        fs::write(data_dir.join("world").join("level.dat"), "old").unwrap();
+        fs::write(data_dir.join("world_nether"), "in the way").unwrap();
+
+        assert!(replace_worlds(&archive, &data_dir).is_err());
+        assert_eq!(
+            fs::read_to_string(data_dir.join("world").join("level.dat")).unwrap(),
+            "old"
+        );
+        assert!(data_dir.join("world_nether").is_file());
+        assert!(!data_dir.join(".restore").exists());
+        assert!(!data_dir.join(".previous").exists());
+
+        fs::remove_file(data_dir.join("world_nether")).unwrap();
+        replace_worlds(&archive, &data_dir).unwrap();
+        assert_eq!(world_dirs(&data_dir).len(), 2);
+        assert_eq!(
+            fs::read_to_string(data_dir.join("world").join("level.dat")).unwrap(),
+            "new"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+use crate::client::error::ClientError;
+use crate::database::models::audit_events::AuditEvents;
+use crate::database::postgresql::PgPool;
+use crate::database::schemas::audit_events::dsl as audit_dsl;
+use crate::util::{EMBED_COLOR, get_pool_from_ctx, parse_key, truncate};
+use diesel::{ExpressionMethods, insert_into};
+use diesel_async::RunQueryDsl;
+use serenity::all::{
+    ChannelId, CommandInteraction, Context, CreateEmbed, CreateMessage, ResolvedOption,
+    ResolvedValue, UserId,
+};
+
+const ERROR_LENGTH: usize = 200;
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditEntry {
+    pub action: String,
+    pub server_name: Option<String>,
+    pub parameters: String,
+}
+
+impl AuditEntry {
+    // The subcommand is part of the action, the `name` option is the server acted upon.
+    #[must_use]
+    pub fn from_command(command: &CommandInteraction) -> Self {
+        let mut entry = Self {
+            action: command.data.name.clone(),
+            ..Self::default()
+        };
+        entry.add_options(&command.data.options());
+        entry
+    }
+
+    fn add_options(&mut self, options: &[ResolvedOption<'_>]) {
+        let mut parameters = Vec::new();
+        for option in options {
+            let value = match &option.value {
+                ResolvedValue::SubCommand(options) | ResolvedValue::SubCommandGroup(options) => {
+                    self.action = format!("{} {}", self.action, option.name);
+                    self.add_options(options);
+                    continue;
+                }
+                ResolvedValue::String("") => continue,
+                ResolvedValue::String(value) if option.name == "name" => {
+                    self.server_name = Some(value.to_lowercase());
+                    continue;
+                }
+                ResolvedValue::String(value) => (*value).to_owned(),
+                ResolvedValue::Integer(value) => value.to_string(),
+                ResolvedValue::Number(value) => value.to_string(),
+                ResolvedValue::Boolean(value) => value.to_string(),
+                ResolvedValue::User(user, _) => format!("<@{}>", user.id),
+                ResolvedValue::Attachment(attachment) => attachment.filename.clone(),
+                _ => continue,
+            };
+            parameters.push(format!("{}={value}", option.name));
+        }
+
+        if !parameters.is_empty() {
+            if !self.parameters.is_empty() {
+                self.parameters.push(' ');
+            }
+            self.parameters.push_str(&parameters.join(" "));
+        }
+    }
+
+    #[must_use]
+    pub fn describe(&self, user_id: i64, error: Option<&str>) -> String {
+        let server = self
+            .server_name
+            .as_ref()
+            .map_or_else(String::new, |name| format!(" sur ``{name}``"));
+        let parameters = if self.parameters.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", self.parameters)
+        };
+        let outcome = error.map_or_else(
+            || "réussi".to_owned(),
+            |error| format!("échec : {}", truncate(error, ERROR_LENGTH)),
+        );
+
+        format!(
+            "<@{user_id}> ``/{}``{server}{parameters} — {outcome}",
+            self.action
+        )
+    }
+}
+
+impl From<&AuditEvents> for AuditEntry {
+    fn from(event: &AuditEvents) -> Self {
+        Self {
+            action: event.action.clone(),
+            server_name: event.server_name.clone(),
+            parameters: event.parameters.clone(),
+        }
+    }
+}
+
+// Recording must never make the command itself fail, errors are only logged.
+pub async fn record(
+    ctx: &Context,
+    user_id: UserId,
+    entry: &AuditEntry,
+    error: Option<&ClientError>,
+) {
+    let error = error.map(ToString::to_string);
+    if let Err(record_error) = insert(ctx, user_id, entry, error.as_deref()).await {
+        log::error!("Unable to record audit event: {record_error}");
+    }
+}
+
+async fn insert(
+    ctx: &Context,
+    user_id: UserId,
+    entry: &AuditEntry,
+    error: Option<&str>,
+) -> Result<(), ClientError> {
+    let user_id = i64::try_from(user_id.get())?;
+
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    insert_into(audit_dsl::audit_events)
+        .values((
+            audit_dsl::user_id.eq(user_id),
+            audit_dsl::action.eq(&entry.action),
+            audit_dsl::server_name.eq(&entry.server_name),
+            audit_dsl::parameters.eq(&entry.parameters),
+            audit_dsl::success.eq(error.is_none()),
+            audit_dsl::error.eq(error),
+        ))
+        .execute(&mut pool.get().await?)
+        .await?;
+
+    let Ok(channel_id) = parse_key::<u64>("AUDIT_CHANNEL_ID") else {
+        return Ok(());
+    };
+
+    let embed = CreateEmbed::new()
+        .description(entry.describe(user_id, error))
+        .color(EMBED_COLOR);
+    ChannelId::new(channel_id)
+        .send_message(&ctx.http, CreateMessage::new().embed(embed))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,298 @@
+use crate::client::error::ClientError;
+use crate::database::models::backups::Backups;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::backup_restores::dsl as restores_dsl;
+use crate::database::schemas::backups::dsl as backups_dsl;
+use crate::runtime::ContainerRuntime;
+use crate::server::archive::{replace_worlds, world_dirs};
+use crate::server::snapshot::snapshot;
+use crate::util::{parse_key, world_dir};
+use chrono::{Datelike, Local, Utc};
+use diesel::{
+    ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, delete, insert_into,
+};
+use diesel_async::RunQueryDsl;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupKind {
+    Scheduled,
+    Manual,
+    Safety,
+}
+
+impl BackupKind {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Manual => "manual",
+            Self::Safety => "safety",
+        }
+    }
+
+    #[must_use]
+    pub fn label(kind: &str) -> &'static str {
+        match kind {
+            "scheduled" => "planifiée",
+            "manual" => "manuelle",
+            "safety" => "avant restauration",
+            _ => "inconnue",
+        }
+    }
+}
+
+struct Retention {
+    last: usize,
+    daily: usize,
+    weekly: usize,
+    safety: usize,
+}
+
+impl Retention {
+    fn from_env() -> Result<Self, ClientError> {
+        let keep = |key: &str, default: usize| {
+            std::env::var(key).map_or(Ok(default), |_| parse_key::<usize>(key))
+        };
+
+        Ok(Self {
+            last: keep("BACKUP_KEEP_LAST", 5)?,
+            daily: keep("BACKUP_KEEP_DAILY", 7)?,
+            weekly: keep("BACKUP_KEEP_WEEKLY", 4)?,
+            safety: keep("BACKUP_KEEP_SAFETY", 3)?,
+        })
+    }
+
+    // `backups` goes from newest to oldest, so the first one seen in a day or week is its newest.
+    // Safety backups only compete with each other, a restore never pushes out a regular backup.
+    fn kept(&self, backups: &[Backups]) -> HashSet<i64> {
+        let (safety, backups): (Vec<&Backups>, Vec<&Backups>) = backups
+            .iter()
+            .partition(|backup| backup.kind == BackupKind::Safety.as_str());
+
+        let mut kept: HashSet<i64> = safety
+            .iter()
+            .take(self.safety)
+            .chain(backups.iter().take(self.last))
+            .map(|backup| backup.id)
+            .collect();
+
+        let mut days = HashSet::new();
+        let mut weeks = HashSet::new();
+        for backup in backups {
+            let date = backup.created_at.with_timezone(&Local).date_naive();
+            if days.len() < self.daily && days.insert(date) {
+                kept.insert(backup.id);
+            }
+            if weeks.len() < self.weekly && weeks.insert(date.iso_week()) {
+                kept.insert(backup.id);
+            }
+        }
+
+        kept
+    }
+}
+
+#[must_use]
+pub fn backup_dir(id: i64) -> PathBuf {
+    Path::new(&std::env::var("BACKUP_DIR").unwrap_or_else(|_| "backups".to_owned()))
+        .join(id.to_string())
+}
+
+pub async fn create_backup(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+    kind: BackupKind,
+) -> Result<Backups, ClientError> {
+    let file_name = format!("{}.tar.gz", Utc::now().format("%Y%m%d-%H%M%S-%3f"));
+    let size = snapshot(conn, runtime, id, backup_dir(id).join(&file_name)).await?;
+
+    let backup: Backups = insert_into(backups_dsl::backups)
+        .values((
+            backups_dsl::server_id.eq(id),
+            backups_dsl::file_name.eq(&file_name),
+            backups_dsl::size.eq(i64::try_from(size)?),
+            backups_dsl::kind.eq(kind.as_str()),
+        ))
+        .returning(Backups::as_returning())
+        .get_result(conn)
+        .await?;
+
+    log::info!("Backup {file_name} of server {id} created ({size} bytes)");
+
+    // A safety snapshot is taken right before a restore, which must not lose its backup to pruning.
+    if kind != BackupKind::Safety
+        && let Err(error) = prune(conn, id).await
+    {
+        log::error!("Unable to prune the backups of server {id}: {error}");
+    }
+
+    Ok(backup)
+}
+
+pub async fn list_backups(conn: &mut PgPooled<'_>, id: i64) -> Result<Vec<Backups>, ClientError> {
+    Ok(backups_dsl::backups
+        .select(Backups::as_select())
+        .filter(backups_dsl::server_id.eq(id))
+        .order_by(backups_dsl::created_at.desc())
+        .load(conn)
+        .await?)
+}
+
+pub async fn prune(conn: &mut PgPooled<'_>, id: i64) -> Result<usize, ClientError> {
+    let backups = list_backups(conn, id).await?;
+    let kept = Retention::from_env()?.kept(&backups);
+
+    let mut removed = 0;
+    for backup in backups.iter().filter(|backup| !kept.contains(&backup.id)) {
+        match fs::remove_file(backup_dir(id).join(&backup.file_name)).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                return Err(error.into());
+            }
+            _ => {}
+        }
+
+        delete(backups_dsl::backups.filter(backups_dsl::id.eq(backup.id)))
+            .execute(conn)
+            .await?;
+        removed += 1;
+    }
+
+    if removed > 0 {
+        log::info!("Pruned {removed} backup(s) of server {id}");
+    }
+
+    Ok(removed)
+}
+
+// The server must be stopped, its current worlds are kept in a safety backup first.
+pub async fn restore_backup(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+    backup_id: i64,
+    user_id: i64,
+) -> Result<Option<Backups>, ClientError> {
+    let backup: Backups = backups_dsl::backups
+        .select(Backups::as_select())
+        .filter(backups_dsl::id.eq(backup_id))
+        .filter(backups_dsl::server_id.eq(id))
+        .get_result(conn)
+        .await
+        .optional()?
+        .ok_or(ClientError::OtherStatic("Cette sauvegarde n'existe pas."))?;
+
+    let archive = backup_dir(id).join(&backup.file_name);
+    if !fs::try_exists(&archive).await? {
+        return Err(ClientError::OtherStatic(
+            "Le fichier de cette sauvegarde est introuvable.",
+        ));
+    }
+
+    let data_dir = world_dir(id).join("data");
+    let safety = if world_dirs(&data_dir).is_empty() {
+        None
+    } else {
+        Some(create_backup(conn, runtime, id, BackupKind::Safety).await?)
+    };
+
+    tokio::task::spawn_blocking(move || replace_worlds(&archive, &data_dir))
+        .await
+        .map_err(|error| ClientError::Other(error.to_string()))??;
+
+    insert_into(restores_dsl::backup_restores)
+        .values((
+            restores_dsl::server_id.eq(id),
+            restores_dsl::backup_id.eq(backup.id),
+            restores_dsl::safety_backup_id.eq(safety.as_ref().map(|safety| safety.id)),
+            restores_dsl::user_id.eq(user_id),
+        ))
+        .execute(conn)
+        .await?;
+
+    log::info!("Server {id} restored from backup {}", backup.file_name);
+
+    Ok(safety)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    fn backup(id: i64, kind: BackupKind, date: &str) -> Backups {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        Backups {
+            id,
+            server_id: 1,
+            file_name: format!("{id}.tar.gz"),
+            size: 0,
+            kind: kind.as_str().to_owned(),
+            created_at: Local
+                .from_local_datetime(&date.and_hms_opt(12, 0, 0).unwrap())
+                .unwrap()
+                .with_timezone(&Utc),
+        }
+    }
+
+    #[test]
+    fn kept() {
+        use BackupKind::{Manual, Safety, Scheduled};
+
+        let same_day = [
+            (1, Scheduled, "2026-10-14"),
+            (2, Manual, "2026-10-14"),
+            (3, Scheduled, "2026-10-14"),
+        ];
+        let days = [
+            (1, Scheduled, "2026-10-14"),
+            (2, Scheduled, "2026-10-14"),
+            (3, Scheduled, "2026-10-13"),
+            (4, Scheduled, "2026-10-12"),
+            (5, Scheduled, "2026-10-12"),
+            (6, Scheduled, "2026-10-11"),
+        ];
+        // The 12th is a Monday, the 11th still belongs to the previous week.
+        let weeks = [
+            (1, Scheduled, "2026-10-14"),
+            (2, Scheduled, "2026-10-12"),
+            (3, Scheduled, "2026-10-11"),
+            (4, Scheduled, "2026-10-05"),
+            (5, Scheduled, "2026-09-28"),
+        ];
+        let restores = [
+            (1, Safety, "2026-10-14"),
+            (2, Scheduled, "2026-10-14"),
+            (3, Safety, "2026-10-13"),
+            (4, Manual, "2026-10-13"),
+        ];
+
+        for (last, daily, weekly, safety, backups, expected) in [
+            (2, 0, 0, 0, &same_day[..], &[1, 2][..]),
+            (0, 3, 0, 0, &days[..], &[1, 3, 4][..]),
+            (1, 3, 0, 0, &days[..], &[1, 3, 4][..]),
+            (0, 0, 2, 0, &weeks[..], &[1, 3][..]),
+            (0, 1, 3, 0, &weeks[..], &[1, 3, 5][..]),
+            (1, 0, 0, 1, &restores[..], &[1, 2][..]),
+            (0, 1, 0, 0, &restores[..], &[2][..]),
+            (0, 0, 0, 5, &restores[..], &[1, 3][..]),
+        ] {
+            let retention = Retention {
+                last,
+                daily,
+                weekly,
+                safety,
+            };
+            let backups: Vec<Backups> = backups
+                .iter()
+                .map(|(id, kind, date)| backup(*id, *kind, date))
+                .collect();
+            let mut kept: Vec<i64> = retention.kept(&backups).into_iter().collect();
+            kept.sort_unstable();
+            assert_eq!(kept, expected, "{last} {daily} {weekly} {safety}");
+        }
+    }
+}
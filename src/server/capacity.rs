@@ -0,0 +1,155 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::runtime::{ContainerRuntime, ContainerStatus};
+use crate::server::compose::read_spec_in;
+use crate::server::lifecycle::ServerStatus;
+use crate::util::{format_memory, parse_key, parse_memory, world_dir};
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use std::path::Path;
+
+pub async fn server_memory(id: i64) -> Result<u64, ClientError> {
+    memory_in(&world_dir(id)).await
+}
+
+// Memory of the server whose files are in `dir`, e.g. in the trash.
+pub async fn memory_in(dir: &Path) -> Result<u64, ClientError> {
+    read_spec_in(dir)
+        .await?
+        .environment
+        .get("MAX_MEMORY")
+        .map_or(Ok(0), |memory| parse_memory(memory))
+}
+
+// A crashed server keeps its memory while its container still runs, e.g. turned unhealthy or not
+// reconciled yet.
+pub async fn holds_resources(
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+    status: ServerStatus,
+) -> Result<bool, ClientError> {
+    Ok(match status {
+        ServerStatus::Starting | ServerStatus::Running | ServerStatus::Stopping => true,
+        ServerStatus::Crashed => !matches!(
+            runtime.status(id).await?,
+            ContainerStatus::Missing | ContainerStatus::Stopped
+        ),
+        ServerStatus::Stopped | ServerStatus::Deleting => false,
+    })
+}
+
+pub async fn check_capacity(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+) -> Result<(), ClientError> {
+    let candidates: Vec<(i64, String, ServerStatus)> = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::name, servers_dsl::status))
+        .filter(
+            servers_dsl::status
+                .eq_any(ServerStatus::ACTIVE)
+                .or(servers_dsl::status.eq(ServerStatus::Crashed)),
+        )
+        .filter(servers_dsl::id.ne(id))
+        .order_by(servers_dsl::name)
+        .load(conn)
+        .await?;
+
+    let mut running: Vec<(i64, String)> = Vec::with_capacity(candidates.len());
+    for (running_id, name, status) in candidates {
+        if holds_resources(runtime, running_id, status).await? {
+            running.push((running_id, name));
+        }
+    }
+
+    let max_running = std::env::var("MAX_RUNNING_SERVERS")
+        .map_or(Ok(1), |_| parse_key::<usize>("MAX_RUNNING_SERVERS"))?;
+
+    if running.len() >= max_running {
+        let names: Vec<String> = running
+            .iter()
+            .map(|(_, name)| format!("``{name}``"))
+            .collect();
+        return Err(ClientError::Other(format!(
+            "Nombre maximal de serveurs lancés atteint ({}/{max_running}) : {}.",
+            running.len(),
+            names.join(", ")
+        )));
+    }
+
+    let Ok(budget) = std::env::var("HOST_MEMORY_BUDGET") else {
+        return Ok(());
+    };
+    let budget = parse_memory(&budget)?;
+
+    let mut used = Vec::with_capacity(running.len());
+    for (running_id, name) in running {
+        used.push((name, server_memory(running_id).await?));
+    }
+    let requested = server_memory(id).await?;
+    let total: u64 = used.iter().map(|(_, memory)| memory).sum::<u64>() + requested;
+
+    if total > budget {
+        let details: Vec<String> = used
+            .iter()
+            .map(|(name, memory)| format!("``{name}`` ({})", format_memory(*memory)))
+            .collect();
+        let details = if details.is_empty() {
+            "aucun".to_owned()
+        } else {
+            details.join(", ")
+        };
+        return Err(ClientError::Other(format!(
+            "Mémoire insuffisante : {} demandés, {} disponibles sur {}. Serveurs lancés : {}.",
+            format_memory(requested),
+            format_memory(budget.saturating_sub(total - requested)),
+            format_memory(budget),
+            details
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::memory::InMemoryRuntime;
+
+    #[tokio::test]
+    async fn crashed_servers_hold_resources_while_their_container_runs() {
+        let runtime = InMemoryRuntime::default();
+
+        for (status, holds) in [
+            (ServerStatus::Stopped, false),
+            (ServerStatus::Starting, true),
+            (ServerStatus::Running, true),
+            (ServerStatus::Stopping, true),
+            (ServerStatus::Crashed, false),
+            (ServerStatus::Deleting, false),
+        ] {
+            assert_eq!(
+                holds_resources(&runtime, 1, status).await.unwrap(),
+                holds,
+                "{status:?}"
+            );
+        }
+
+        for (container, holds) in [
+            (ContainerStatus::Stopped, false),
+            (ContainerStatus::Starting, true),
+            (ContainerStatus::Running, true),
+            (ContainerStatus::Unhealthy, true),
+        ] {
+            runtime.set_status(1, container);
+            assert_eq!(
+                holds_resources(&runtime, 1, ServerStatus::Crashed)
+                    .await
+                    .unwrap(),
+                holds,
+                "{container:?}"
+            );
+        }
+    }
+}
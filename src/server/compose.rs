@@ -0,0 +1,132 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::server::{addons, ops, whitelist};
+use crate::util::{rcon_bind, world_dir};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::fs;
+
+pub const SERVICE: &str = "mc";
+pub const RCON_PORT: u16 = 25575;
+
+#[derive(Serialize, Deserialize)]
+pub struct ComposeFile {
+    pub services: BTreeMap<String, ServiceSpec>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ServiceSpec {
+    pub image: String,
+    #[serde(default)]
+    pub tty: bool,
+    #[serde(default)]
+    pub stdin_open: bool,
+    #[serde(default)]
+    pub ports: Vec<String>,
+    #[serde(default)]
+    pub environment: BTreeMap<String, String>,
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    pub healthcheck: Option<HealthcheckSpec>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HealthcheckSpec {
+    pub test: String,
+    pub start_period: String,
+    pub interval: String,
+    pub retries: String,
+}
+
+pub async fn read_compose(id: i64) -> Result<ComposeFile, ClientError> {
+    read_compose_in(&world_dir(id)).await
+}
+
+pub async fn read_compose_in(dir: &Path) -> Result<ComposeFile, ClientError> {
+    let yml = fs::read_to_string(dir.join("docker-compose.yml")).await?;
+    Ok(serde_yml::from_str::<ComposeFile>(&yml)?)
+}
+
+pub async fn write_compose(id: i64, compose: &ComposeFile) -> Result<(), ClientError> {
+    fs::write(
+        world_dir(id).join("docker-compose.yml"),
+        serde_yml::to_string(compose)?,
+    )
+    .await?;
+    Ok(())
+}
+
+pub async fn read_spec(id: i64) -> Result<ServiceSpec, ClientError> {
+    read_spec_in(&world_dir(id)).await
+}
+
+pub async fn read_spec_in(dir: &Path) -> Result<ServiceSpec, ClientError> {
+    read_compose_in(dir)
+        .await?
+        .services
+        .remove(SERVICE)
+        .ok_or_else(|| {
+            ClientError::Other(format!("Service `{SERVICE}` absent de {}.", dir.display()))
+        })
+}
+
+#[must_use]
+pub fn rcon_binding(bind: &str, host_port: &str) -> String {
+    format!("{bind}:{host_port}:{RCON_PORT}")
+}
+
+// Servers created before `RCON_BIND` published RCON on every interface.
+// The image only adds players to an existing list file, such as `whitelist.json`, unless told to
+// synchronise it with its variable, so removals would never be applied.
+pub fn synchronize_file(environment: &mut BTreeMap<String, String>, list: &str) {
+    environment.insert(format!("EXISTING_{list}_FILE"), "SYNCHRONIZE".to_owned());
+}
+
+fn bind_rcon(ports: &mut [String], bind: &str) {
+    let suffix = format!(":{RCON_PORT}");
+    for port in ports {
+        if let Some(published) = port.strip_suffix(&suffix) {
+            let host_port = published.rsplit(':').next().unwrap_or(published);
+            *port = rcon_binding(bind, host_port);
+        }
+    }
+}
+
+// Brings the compose file up to date with the settings stored in the database, before a start.
+pub async fn render(conn: &mut PgPooled<'_>, id: i64) -> Result<(), ClientError> {
+    let mut compose = read_compose(id).await?;
+    let spec = compose.services.get_mut(SERVICE).ok_or_else(|| {
+        ClientError::Other(format!("Service `{SERVICE}` absent du serveur {id}."))
+    })?;
+
+    bind_rcon(&mut spec.ports, &rcon_bind()?);
+    addons::apply(conn, id, &mut spec.environment).await?;
+    whitelist::apply(conn, id, &mut spec.environment).await?;
+    ops::apply(conn, id, &mut spec.environment).await?;
+
+    write_compose(id, &compose).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bind_rcon_only_rewrites_the_rcon_port() {
+        let mut ports = vec![
+            "10000:25565".to_owned(),
+            "10001:25575".to_owned(),
+            "0.0.0.0:10003:25575".to_owned(),
+        ];
+        bind_rcon(&mut ports, "127.0.0.1");
+        assert_eq!(
+            ports,
+            [
+                "10000:25565",
+                "127.0.0.1:10001:25575",
+                "127.0.0.1:10003:25575"
+            ]
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::protocol::rcon::RconClient;
+use crate::runtime::ContainerRuntime;
+use crate::util::mc_host;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn run_command(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+    command: &str,
+) -> Result<String, ClientError> {
+    let (rcon_port, rcon_password): (Option<i64>, Option<String>) = servers_dsl::servers
+        .select((servers_dsl::rcon_port, servers_dsl::rcon_password))
+        .filter(servers_dsl::id.eq(id))
+        .get_result(conn)
+        .await?;
+
+    // Servers created before RCON was exposed still ship the image's own client.
+    let (Some(rcon_port), Some(rcon_password)) = (rcon_port, rcon_password) else {
+        return runtime.exec(id, &["rcon-cli", command]).await;
+    };
+
+    let host = mc_host()?;
+    tokio::time::timeout(TIMEOUT, async {
+        RconClient::connect(&host, u16::try_from(rcon_port)?, &rcon_password)
+            .await?
+            .command(command)
+            .await
+    })
+    .await
+    .map_err(|_| ClientError::ProtocolError("La console RCON ne répond pas.".to_owned()))?
+}
+
+// Values that must never be echoed back to Discord, such as in the container logs.
+pub async fn secrets(conn: &mut PgPooled<'_>, id: i64) -> Result<Vec<String>, ClientError> {
+    let rcon_password: Option<String> = servers_dsl::servers
+        .select(servers_dsl::rcon_password)
+        .filter(servers_dsl::id.eq(id))
+        .get_result(conn)
+        .await?;
+
+    Ok(rcon_password.into_iter().collect())
+}
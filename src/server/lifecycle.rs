@@ -0,0 +1,347 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::runtime::{ContainerRuntime, ContainerStatus};
+use crate::server::compose::render;
+use crate::server::console::secrets;
+use crate::server::quota::track_session;
+use crate::util::{redact, truncate_start};
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use std::io::Write;
+
+const FAILURE_LOG_LINES: u32 = 30;
+const FAILURE_LOG_LENGTH: usize = 1500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub enum ServerStatus {
+    Stopped,
+    Starting,
+    Running,
+    Stopping,
+    Crashed,
+    Deleting,
+}
+
+impl ServerStatus {
+    pub const ACTIVE: [Self; 3] = [Self::Starting, Self::Running, Self::Stopping];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Stopped => "stopped",
+            Self::Starting => "starting",
+            Self::Running => "running",
+            Self::Stopping => "stopping",
+            Self::Crashed => "crashed",
+            Self::Deleting => "deleting",
+        }
+    }
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Stopped => "arrêté",
+            Self::Starting => "démarrage en cours",
+            Self::Running => "lancé",
+            Self::Stopping => "arrêt en cours",
+            Self::Crashed => "planté",
+            Self::Deleting => "suppression en cours",
+        }
+    }
+
+    #[must_use]
+    pub const fn can_become(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (
+                Self::Stopped | Self::Crashed,
+                Self::Starting | Self::Deleting
+            ) | (Self::Starting, Self::Running | Self::Crashed)
+                | (Self::Running | Self::Crashed, Self::Stopping)
+                | (
+                    Self::Stopping,
+                    Self::Stopped | Self::Starting | Self::Crashed
+                )
+                | (Self::Deleting, Self::Stopped)
+        )
+    }
+
+    #[must_use]
+    pub const fn refusal(self) -> &'static str {
+        match self {
+            Self::Stopped => "Le serveur n'est pas lancé.",
+            Self::Starting => "Le serveur est en cours de démarrage.",
+            Self::Running => "Le serveur est lancé.",
+            Self::Stopping => "Le serveur est en cours d'arrêt.",
+            Self::Crashed => "Le serveur a planté, arrêtez-le d'abord.",
+            Self::Deleting => "Le serveur est en cours de suppression.",
+        }
+    }
+}
+
+impl ToSql<Text, Pg> for ServerStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for ServerStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match <String as FromSql<Text, Pg>>::from_sql(bytes)?.as_str() {
+            "stopped" => Ok(Self::Stopped),
+            "starting" => Ok(Self::Starting),
+            "running" => Ok(Self::Running),
+            "stopping" => Ok(Self::Stopping),
+            "crashed" => Ok(Self::Crashed),
+            "deleting" => Ok(Self::Deleting),
+            other => Err(format!("Unknown server status `{other}`").into()),
+        }
+    }
+}
+
+pub async fn transition(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    next: ServerStatus,
+) -> Result<ServerStatus, ClientError> {
+    let current: ServerStatus = servers_dsl::servers
+        .select(servers_dsl::status)
+        .filter(servers_dsl::id.eq(id))
+        .get_result(conn)
+        .await?;
+
+    if !current.can_become(next) {
+        return Err(ClientError::OtherStatic(current.refusal()));
+    }
+
+    if !compare_and_set(conn, id, current, next).await? {
+        return Err(ClientError::OtherStatic(
+            "L'état du serveur a changé entre-temps, réessayez.",
+        ));
+    }
+
+    Ok(current)
+}
+
+pub async fn compare_and_set(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    current: ServerStatus,
+    next: ServerStatus,
+) -> Result<bool, ClientError> {
+    let updated = diesel::update(
+        servers_dsl::servers
+            .filter(servers_dsl::id.eq(id))
+            .filter(servers_dsl::status.eq(current)),
+    )
+    .set(servers_dsl::status.eq(next))
+    .execute(conn)
+    .await?;
+
+    if updated == 1 {
+        track_session(conn, id, current, next).await?;
+    }
+
+    Ok(updated == 1)
+}
+
+pub async fn boot(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+) -> Result<(), ClientError> {
+    if let Err(error) = render(conn, id).await {
+        log::error!("Server {id} could not be configured: {error}");
+        transition(conn, id, ServerStatus::Crashed).await?;
+        return Err(error);
+    }
+
+    if let Err(error) = runtime.up(id).await {
+        log::error!("Server {id} failed to start: {error}");
+
+        // Without the secrets the logs cannot be redacted, so they are not shown at all.
+        let logs = match (
+            runtime.logs(id, FAILURE_LOG_LINES).await,
+            secrets(conn, id).await,
+        ) {
+            (Ok(logs), Ok(secrets)) => {
+                truncate_start(&redact(logs.trim(), &secrets), FAILURE_LOG_LENGTH)
+            }
+            (Err(logs_error), _) | (_, Err(logs_error)) => {
+                format!("(logs indisponibles : {logs_error})")
+            }
+        };
+
+        // Do not leave a half-started container behind holding the port and memory.
+        if let Err(down_error) = runtime.down(id).await {
+            log::error!("Unable to roll back server {id} after a failed start: {down_error}");
+        }
+
+        transition(conn, id, ServerStatus::Crashed).await?;
+        return Err(ClientError::StartError(error.to_string(), logs));
+    }
+
+    transition(conn, id, ServerStatus::Running).await?;
+    Ok(())
+}
+
+// `next` is `Starting` when the server is restarted, so that it keeps its running slot.
+pub async fn halt(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+    next: ServerStatus,
+) -> Result<(), ClientError> {
+    if let Err(error) = runtime.down(id).await {
+        transition(conn, id, ServerStatus::Crashed).await?;
+        return Err(error);
+    }
+
+    transition(conn, id, next).await?;
+    Ok(())
+}
+
+// A crashed server may still have a live container, e.g. one that turned unhealthy.
+pub async fn check_container_stopped(
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+) -> Result<(), ClientError> {
+    match runtime.status(id).await? {
+        ContainerStatus::Missing | ContainerStatus::Stopped => Ok(()),
+        _ => Err(ClientError::OtherStatic(
+            "Le conteneur du serveur tourne encore, arrêtez-le d'abord.",
+        )),
+    }
+}
+
+// Status that the database should hold given what the container runtime reports, used to
+// repair drift and operations interrupted by a bot restart.
+#[must_use]
+pub const fn reconciled(current: ServerStatus, container: ContainerStatus) -> Option<ServerStatus> {
+    match (current, container) {
+        (ServerStatus::Running, ContainerStatus::Running | ContainerStatus::Starting)
+        | (ServerStatus::Stopped | ServerStatus::Crashed, ContainerStatus::Missing)
+        | (ServerStatus::Crashed, ContainerStatus::Stopped | ContainerStatus::Unhealthy)
+        | (ServerStatus::Deleting, _) => None,
+        (_, ContainerStatus::Running | ContainerStatus::Starting) => Some(ServerStatus::Running),
+        (_, ContainerStatus::Missing) => Some(ServerStatus::Stopped),
+        (_, ContainerStatus::Stopped | ContainerStatus::Unhealthy) => Some(ServerStatus::Crashed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [ServerStatus; 6] = [
+        ServerStatus::Stopped,
+        ServerStatus::Starting,
+        ServerStatus::Running,
+        ServerStatus::Stopping,
+        ServerStatus::Crashed,
+        ServerStatus::Deleting,
+    ];
+
+    #[test]
+    fn allowed_transitions() {
+        use ServerStatus::{Crashed, Deleting, Running, Starting, Stopped, Stopping};
+
+        let allowed = [
+            (Stopped, Starting),
+            (Stopped, Deleting),
+            (Crashed, Starting),
+            (Crashed, Deleting),
+            (Crashed, Stopping),
+            (Starting, Running),
+            (Starting, Crashed),
+            (Running, Stopping),
+            (Stopping, Stopped),
+            (Stopping, Starting),
+            (Stopping, Crashed),
+            (Deleting, Stopped),
+        ];
+
+        for current in ALL {
+            for next in ALL {
+                assert_eq!(
+                    current.can_become(next),
+                    allowed.contains(&(current, next)),
+                    "{current:?} -> {next:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reconciled_status() {
+        use ContainerStatus as C;
+        use ServerStatus as S;
+
+        let cases = [
+            (S::Running, C::Running, None),
+            (S::Running, C::Starting, None),
+            (S::Running, C::Missing, Some(S::Stopped)),
+            (S::Running, C::Stopped, Some(S::Crashed)),
+            (S::Running, C::Unhealthy, Some(S::Crashed)),
+            (S::Stopped, C::Missing, None),
+            (S::Stopped, C::Running, Some(S::Running)),
+            (S::Stopped, C::Stopped, Some(S::Crashed)),
+            (S::Crashed, C::Missing, None),
+            (S::Crashed, C::Stopped, None),
+            (S::Crashed, C::Unhealthy, None),
+            (S::Crashed, C::Running, Some(S::Running)),
+            (S::Starting, C::Starting, Some(S::Running)),
+            (S::Starting, C::Missing, Some(S::Stopped)),
+            (S::Stopping, C::Missing, Some(S::Stopped)),
+            (S::Stopping, C::Running, Some(S::Running)),
+            (S::Deleting, C::Running, None),
+            (S::Deleting, C::Missing, None),
+        ];
+
+        for (current, container, expected) in cases {
+            assert_eq!(
+                reconciled(current, container),
+                expected,
+                "{current:?} with {container:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn crashed_servers_need_a_stopped_container() {
+        let runtime = crate::runtime::memory::InMemoryRuntime::default();
+        assert!(check_container_stopped(&runtime, 1).await.is_ok());
+
+        for (status, stopped) in [
+            (ContainerStatus::Stopped, true),
+            (ContainerStatus::Starting, false),
+            (ContainerStatus::Running, false),
+            (ContainerStatus::Unhealthy, false),
+        ] {
+            runtime.set_status(1, status);
+            assert_eq!(
+                check_container_stopped(&runtime, 1).await.is_ok(),
+                stopped,
+                "{status:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn status_round_trips_through_its_name() {
+        for status in ALL {
+            let parsed = ALL
+                .into_iter()
+                .find(|other| other.as_str() == status.as_str());
+            assert_eq!(parsed, Some(status));
+        }
+    }
+}
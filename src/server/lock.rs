@@ -0,0 +1,59 @@
+use crate::client::data::ServerLocksData;
+use crate::client::error::ClientError;
+use serenity::all::Context;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OwnedMutexGuard;
+
+#[derive(Default)]
+pub struct ServerLocks {
+    locked: Mutex<HashSet<i64>>,
+    starts: Arc<tokio::sync::Mutex<()>>,
+}
+
+pub struct ServerGuard {
+    locks: Arc<ServerLocks>,
+    id: i64,
+}
+
+impl ServerLocks {
+    #[must_use]
+    pub fn try_lock(self: &Arc<Self>, id: i64) -> Option<ServerGuard> {
+        self.locked.lock().unwrap().insert(id).then(|| ServerGuard {
+            locks: Arc::clone(self),
+            id,
+        })
+    }
+}
+
+impl Drop for ServerGuard {
+    fn drop(&mut self) {
+        self.locks.locked.lock().unwrap().remove(&self.id);
+    }
+}
+
+pub async fn get_locks_from_ctx(ctx: &Context) -> Result<Arc<ServerLocks>, ClientError> {
+    ctx.data
+        .read()
+        .await
+        .get::<ServerLocksData>()
+        .ok_or(ClientError::OtherStatic("Missing ServerLocksData in data."))
+        .cloned()
+}
+
+pub async fn lock_server(ctx: &Context, id: i64) -> Result<ServerGuard, ClientError> {
+    get_locks_from_ctx(ctx)
+        .await?
+        .try_lock(id)
+        .ok_or(ClientError::OtherStatic(
+            "Une opération est déjà en cours sur ce serveur.",
+        ))
+}
+
+// Capacity and quotas count the servers already starting, so the check and the transition that
+// claims the slot must not interleave with another start.
+pub async fn lock_starts(ctx: &Context) -> Result<OwnedMutexGuard<()>, ClientError> {
+    Ok(Arc::clone(&get_locks_from_ctx(ctx).await?.starts)
+        .lock_owned()
+        .await)
+}
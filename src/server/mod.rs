@@ -0,0 +1,17 @@
+pub mod addons;
+pub mod archive;
+pub mod audit;
+pub mod backup;
+pub mod capacity;
+pub mod compose;
+pub mod console;
+pub mod lifecycle;
+pub mod lock;
+pub mod ops;
+pub mod permission;
+pub mod provision;
+pub mod quota;
+pub mod snapshot;
+pub mod software;
+pub mod trash;
+pub mod whitelist;
@@ -0,0 +1,155 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::minecraft_accounts::dsl as accounts_dsl;
+use crate::database::schemas::server_ops::dsl as ops_dsl;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::compose::synchronize_file;
+use crate::util::world_dir;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use std::collections::BTreeMap;
+use tokio::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpSource {
+    Default,
+    Owner,
+    Added,
+}
+
+impl OpSource {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Default => "par défaut",
+            Self::Owner => "propriétaire",
+            Self::Added => "ajouté",
+        }
+    }
+}
+
+// Players listed in `ADMIN_PLAYER`, separated by commas, are op on every server.
+#[must_use]
+pub fn default_ops() -> Vec<String> {
+    std::env::var("ADMIN_PLAYER")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|player| !player.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+pub async fn linked_player(
+    conn: &mut PgPooled<'_>,
+    user_id: i64,
+) -> Result<Option<String>, ClientError> {
+    Ok(accounts_dsl::minecraft_accounts
+        .select(accounts_dsl::player)
+        .filter(accounts_dsl::user_id.eq(user_id))
+        .get_result(conn)
+        .await
+        .optional()?)
+}
+
+// Every op of the server with where it comes from. A player given op by several sources is listed
+// once, except for the added rows which must stay visible to remain removable.
+pub async fn ops(conn: &mut PgPooled<'_>, id: i64) -> Result<Vec<(String, OpSource)>, ClientError> {
+    let mut ops: Vec<(String, OpSource)> = default_ops()
+        .into_iter()
+        .map(|player| (player, OpSource::Default))
+        .collect();
+
+    let owner: Option<i64> = servers_dsl::servers
+        .select(servers_dsl::owner_id)
+        .filter(servers_dsl::id.eq(id))
+        .get_result(conn)
+        .await?;
+    if let Some(owner) = owner
+        && let Some(player) = linked_player(conn, owner).await?
+    {
+        ops.push((player, OpSource::Owner));
+    }
+
+    let added: Vec<String> = ops_dsl::server_ops
+        .select(ops_dsl::player)
+        .filter(ops_dsl::server_id.eq(id))
+        .order_by(ops_dsl::player)
+        .load(conn)
+        .await?;
+    ops.extend(added.into_iter().map(|player| (player, OpSource::Added)));
+
+    Ok(dedupe(ops))
+}
+
+fn dedupe(ops: Vec<(String, OpSource)>) -> Vec<(String, OpSource)> {
+    let mut unique: Vec<(String, OpSource)> = Vec::with_capacity(ops.len());
+    for (player, source) in ops {
+        if source == OpSource::Added
+            || !unique
+                .iter()
+                .any(|(known, _)| known.eq_ignore_ascii_case(&player))
+        {
+            unique.push((player, source));
+        }
+    }
+    unique
+}
+
+pub async fn apply(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    environment: &mut BTreeMap<String, String>,
+) -> Result<(), ClientError> {
+    let mut players: Vec<String> = Vec::new();
+    for (player, _) in ops(conn, id).await? {
+        if !players
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(&player))
+        {
+            players.push(player);
+        }
+    }
+
+    // Without any op the image leaves `ops.json` alone, so it is removed here.
+    if players.is_empty() {
+        environment.remove("OPS");
+        match fs::remove_file(world_dir(id).join("data").join("ops.json")).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => return Err(error.into()),
+            _ => {}
+        }
+    } else {
+        environment.insert("OPS".to_owned(), players.join(","));
+    }
+    synchronize_file(environment, "OPS");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedupe_keeps_added_rows() {
+        let ops = [
+            ("Arei22", OpSource::Default),
+            ("arei22", OpSource::Owner),
+            ("AREI22", OpSource::Added),
+            ("Steve", OpSource::Owner),
+            ("Alex", OpSource::Added),
+        ]
+        .map(|(player, source)| (player.to_owned(), source));
+
+        assert_eq!(
+            dedupe(ops.to_vec()),
+            [
+                ("Arei22", OpSource::Default),
+                ("AREI22", OpSource::Added),
+                ("Steve", OpSource::Owner),
+                ("Alex", OpSource::Added),
+            ]
+            .map(|(player, source)| (player.to_owned(), source))
+        );
+    }
+}
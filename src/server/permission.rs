@@ -0,0 +1,104 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::server_members::dsl as members_dsl;
+use crate::util::parse_key;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::{Member, Permissions, RoleId, UserId};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemberRole {
+    Player,
+    Operator,
+    Owner,
+}
+
+impl MemberRole {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Player => "player",
+            Self::Operator => "operator",
+            Self::Owner => "owner",
+        }
+    }
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Player => "joueur",
+            Self::Operator => "opérateur",
+            Self::Owner => "propriétaire",
+        }
+    }
+}
+
+impl FromStr for MemberRole {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "player" => Ok(Self::Player),
+            "operator" => Ok(Self::Operator),
+            "owner" => Ok(Self::Owner),
+            other => Err(format!("Rôle inconnu : {other}.")),
+        }
+    }
+}
+
+// Guild administrators, and members of the Discord role set in `ADMIN_ROLE`, may manage every
+// server.
+pub fn is_admin(member: Option<&Member>) -> Result<bool, ClientError> {
+    let Some(member) = member else {
+        return Ok(false);
+    };
+    if member.permissions.is_some_and(Permissions::administrator) {
+        return Ok(true);
+    }
+
+    let Some(role) = std::env::var("ADMIN_ROLE")
+        .map_or(Ok(None), |_| parse_key::<u64>("ADMIN_ROLE").map(Some))?
+    else {
+        return Ok(false);
+    };
+
+    Ok(member.roles.contains(&RoleId::new(role)))
+}
+
+pub async fn member_role(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    user_id: UserId,
+) -> Result<Option<MemberRole>, ClientError> {
+    let role: Option<String> = members_dsl::server_members
+        .select(members_dsl::role)
+        .filter(members_dsl::server_id.eq(id))
+        .filter(members_dsl::user_id.eq(i64::try_from(user_id.get())?))
+        .get_result(conn)
+        .await
+        .optional()?;
+
+    role.map(|role| role.parse().map_err(ClientError::Other))
+        .transpose()
+}
+
+pub async fn require(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    user_id: UserId,
+    member: Option<&Member>,
+    needed: MemberRole,
+) -> Result<(), ClientError> {
+    if is_admin(member)? {
+        return Ok(());
+    }
+
+    match member_role(conn, id, user_id).await? {
+        Some(role) if role >= needed => Ok(()),
+        _ => Err(ClientError::Other(format!(
+            "Il faut être {} de ce serveur pour faire cela.",
+            needed.label()
+        ))),
+    }
+}
@@ -0,0 +1,273 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::server_members::dsl as members_dsl;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::compose::rcon_binding;
+use crate::server::lifecycle::ServerStatus;
+use crate::server::permission::MemberRole;
+use crate::server::software::ServerType;
+use crate::util::{parse_key, parse_memory, rcon_bind, world_dir};
+use diesel::dsl::exists;
+use diesel::{ExpressionMethods, QueryDsl, insert_into};
+use diesel_async::RunQueryDsl;
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use serde_yml::Mapping;
+use serde_yml::Value;
+use serenity::all::UserId;
+use tokio::fs;
+
+const RCON_PASSWORD_LENGTH: usize = 32;
+
+const MAX_NAME_LENGTH: usize = 25;
+
+// Names end up in file names and Discord messages, so they are kept to a safe alphabet.
+#[must_use]
+pub fn is_valid_name(name: &str) -> bool {
+    (1..=MAX_NAME_LENGTH).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+pub async fn check_name(conn: &mut PgPooled<'_>, name: &str) -> Result<(), ClientError> {
+    if !is_valid_name(name) {
+        return Err(ClientError::Other(format!(
+            "Le nom du serveur doit faire au plus {MAX_NAME_LENGTH} caractères parmi a-z, 0-9, _ et -."
+        )));
+    }
+
+    let serv_exist: bool = diesel::select(exists(
+        servers_dsl::servers
+            .filter(servers_dsl::name.eq(name))
+            .filter(servers_dsl::deleted_at.is_null()),
+    ))
+    .get_result(conn)
+    .await?;
+    if serv_exist {
+        return Err(ClientError::OtherStatic("Ce nom de serveur existe déjà."));
+    }
+    Ok(())
+}
+
+// Inserts the server row and writes its compose file, returning the id of the new server.
+// Memory given to new servers.
+pub fn default_memory() -> Result<u64, ClientError> {
+    parse_memory(&parse_key::<String>("MAX_MEMORY")?)
+}
+
+pub async fn create_server(
+    conn: &mut PgPooled<'_>,
+    name: &str,
+    version: Option<&str>,
+    difficulty: Option<&str>,
+    server_type: ServerType,
+    owner: UserId,
+) -> Result<i64, ClientError> {
+    let owner_id = i64::try_from(owner.get())?;
+
+    check_name(conn, name).await?;
+
+    let mut ports_used: Vec<i64> = servers_dsl::servers
+        .select(servers_dsl::port)
+        .filter(servers_dsl::deleted_at.is_null())
+        .order_by(servers_dsl::port)
+        .load::<i64>(conn)
+        .await?;
+    ports_used.extend(
+        servers_dsl::servers
+            .select(servers_dsl::rcon_port)
+            .filter(servers_dsl::rcon_port.is_not_null())
+            .filter(servers_dsl::deleted_at.is_null())
+            .load::<Option<i64>>(conn)
+            .await?
+            .into_iter()
+            .flatten(),
+    );
+
+    let mut free_ports = (parse_key::<i64>("MIN_PORT")?..parse_key::<i64>("MAX_PORT")?)
+        .filter(|port| !ports_used.contains(port));
+
+    let (Some(port), Some(rcon_port)) = (free_ports.next(), free_ports.next()) else {
+        return Err(ClientError::OtherStatic("Pas de port disponible."));
+    };
+
+    let rcon_password: String = rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(RCON_PASSWORD_LENGTH)
+        .map(char::from)
+        .collect();
+
+    let mut services = Mapping::new();
+
+    let mut mc = Mapping::new();
+    mc.insert(
+        Value::String("image".into()),
+        Value::String("itzg/minecraft-server".into()),
+    );
+    mc.insert(Value::String("tty".into()), Value::Bool(true));
+    mc.insert(Value::String("stdin_open".into()), Value::Bool(true));
+    mc.insert(
+        Value::String("ports".into()),
+        Value::Sequence(vec![
+            Value::String(format!("{port}:25565")),
+            Value::String(rcon_binding(&rcon_bind()?, &rcon_port.to_string())),
+        ]),
+    );
+
+    let mut env = Mapping::new();
+
+    env.insert(Value::String("EULA".into()), Value::String("TRUE".into()));
+
+    env.insert(
+        Value::String("TYPE".into()),
+        Value::String(server_type.env_value().into()),
+    );
+
+    env.insert(
+        Value::String("ENABLE_RCON".into()),
+        Value::String("TRUE".into()),
+    );
+    env.insert(
+        Value::String("RCON_PASSWORD".into()),
+        Value::String(rcon_password.clone()),
+    );
+
+    if let Some(version) = version {
+        let json = fs::read_to_string("versions.json").await?;
+        let versions: Vec<String> = serde_json::from_str(&json)?;
+
+        if !versions.iter().any(|e| e == version) {
+            return Err(ClientError::Other(format!(
+                "{version} n'est pas une version valide."
+            )));
+        }
+        if !server_type.supports_version(version) {
+            return Err(ClientError::Other(format!(
+                "{} n'est pas disponible en {version} (minimum {}).",
+                server_type.label(),
+                server_type.min_version().unwrap_or_default()
+            )));
+        }
+
+        env.insert(
+            Value::String("VERSION".into()),
+            Value::String(version.into()),
+        );
+    }
+
+    if let Some(difficulty) = difficulty {
+        env.insert(
+            Value::String("DIFFICULTY".into()),
+            Value::String(difficulty.into()),
+        );
+    }
+
+    env.insert(
+        Value::String("MAX_MEMORY".into()),
+        Value::String(parse_key::<String>("MAX_MEMORY")?),
+    );
+
+    mc.insert(Value::String("environment".into()), Value::Mapping(env));
+
+    mc.insert(
+        Value::String("volumes".into()),
+        Value::Sequence(vec![Value::String("./data:/data".into())]),
+    );
+
+    let mut healthcheck = Mapping::new();
+
+    healthcheck.insert(
+        Value::String("test".into()),
+        Value::String("mc-health".into()),
+    );
+    healthcheck.insert(
+        Value::String("start_period".into()),
+        Value::String("1m".into()),
+    );
+    healthcheck.insert(Value::String("interval".into()), Value::String("5s".into()));
+    healthcheck.insert(Value::String("retries".into()), Value::String("20".into()));
+
+    mc.insert("healthcheck".into(), Value::Mapping(healthcheck));
+
+    services.insert(Value::String("mc".into()), Value::Mapping(mc));
+
+    let mut root = Mapping::new();
+    root.insert(Value::String("services".into()), Value::Mapping(services));
+
+    let yml_str = serde_yml::to_string(&root)?;
+
+    let id: i64 = insert_into(servers_dsl::servers)
+        .values((
+            servers_dsl::name.eq(name),
+            servers_dsl::version.eq(version.unwrap_or("latest").to_string()),
+            servers_dsl::difficulty.eq(difficulty.unwrap_or("easy")),
+            servers_dsl::port.eq(port),
+            servers_dsl::status.eq(ServerStatus::Stopped),
+            servers_dsl::rcon_port.eq(rcon_port),
+            servers_dsl::rcon_password.eq(&rcon_password),
+            servers_dsl::server_type.eq(server_type),
+            servers_dsl::owner_id.eq(owner_id),
+        ))
+        .returning(servers_dsl::id)
+        .get_result(conn)
+        .await?;
+
+    insert_into(members_dsl::server_members)
+        .values((
+            members_dsl::server_id.eq(id),
+            members_dsl::user_id.eq(owner_id),
+            members_dsl::role.eq(MemberRole::Owner.as_str()),
+        ))
+        .execute(conn)
+        .await?;
+
+    let dir = world_dir(id);
+
+    fs::create_dir_all(&dir).await?;
+    fs::write(dir.join("docker-compose.yml"), yml_str).await?;
+
+    Ok(id)
+}
+
+// Undoes `create_server` when the rest of a creation fails.
+pub async fn discard_server(conn: &mut PgPooled<'_>, id: i64) -> Result<(), ClientError> {
+    diesel::delete(servers_dsl::servers.filter(servers_dsl::id.eq(id)))
+        .execute(conn)
+        .await?;
+
+    match fs::remove_dir_all(world_dir(id)).await {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_names() {
+        for name in [
+            "survie",
+            "survie-2",
+            "mon_monde",
+            "a",
+            "abcdefghijklmnopqrstuvwxy",
+        ] {
+            assert!(is_valid_name(name), "{name}");
+        }
+        for name in [
+            "",
+            "../../etc/x",
+            "/etc",
+            "a.b",
+            "Survie",
+            "é",
+            "with space",
+            "abcdefghijklmnopqrstuvwxyz",
+        ] {
+            assert!(!is_valid_name(name), "{name}");
+        }
+    }
+}
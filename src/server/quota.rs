@@ -0,0 +1,228 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::server_sessions::dsl as sessions_dsl;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::runtime::ContainerRuntime;
+use crate::server::capacity::{holds_resources, server_memory};
+use crate::server::lifecycle::ServerStatus;
+use crate::util::{format_memory, parse_key, parse_memory};
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, insert_into};
+use diesel_async::RunQueryDsl;
+use serenity::all::UserId;
+use std::str::FromStr;
+
+// Limits applied to every user, a missing variable means no limit.
+#[derive(Debug, Clone, Copy)]
+pub struct Quota {
+    pub max_servers: Option<usize>,
+    pub max_memory: Option<u64>,
+    pub max_running: Option<usize>,
+    pub hours_per_day: Option<i64>,
+}
+
+impl Quota {
+    pub fn from_env() -> Result<Self, ClientError> {
+        Ok(Self {
+            max_servers: optional_key("QUOTA_MAX_SERVERS")?,
+            max_memory: std::env::var("QUOTA_MAX_MEMORY")
+                .ok()
+                .map(|memory| parse_memory(&memory))
+                .transpose()?,
+            max_running: optional_key("QUOTA_MAX_RUNNING")?,
+            hours_per_day: optional_key("QUOTA_HOURS_PER_DAY")?,
+        })
+    }
+}
+
+fn optional_key<T: FromStr>(key: &str) -> Result<Option<T>, ClientError>
+where
+    <T as FromStr>::Err: std::fmt::Display,
+{
+    std::env::var(key).map_or(Ok(None), |_| parse_key::<T>(key).map(Some))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Usage {
+    pub servers: usize,
+    pub memory: u64,
+    pub running: usize,
+    pub today: TimeDelta,
+}
+
+// What the servers owned by `owner` currently use, leaving out the server `except` if given.
+pub async fn usage(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    owner: i64,
+    except: Option<i64>,
+) -> Result<Usage, ClientError> {
+    let servers: Vec<(i64, ServerStatus)> = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::status))
+        .filter(servers_dsl::owner_id.eq(owner))
+        .filter(servers_dsl::deleted_at.is_null())
+        .load(conn)
+        .await?;
+
+    let mut memory = 0;
+    let mut running = 0;
+    for (id, status) in &servers {
+        if Some(*id) != except && holds_resources(runtime, *id, *status).await? {
+            memory += server_memory(*id).await?;
+            running += 1;
+        }
+    }
+
+    Ok(Usage {
+        servers: servers.len(),
+        memory,
+        running,
+        today: played_today(conn, owner).await?,
+    })
+}
+
+pub async fn played_today(conn: &mut PgPooled<'_>, owner: i64) -> Result<TimeDelta, ClientError> {
+    let midnight: DateTime<Utc> = Local::now()
+        .date_naive()
+        .and_time(chrono::NaiveTime::MIN)
+        .and_local_timezone(Local)
+        .earliest()
+        .map_or_else(Utc::now, |midnight| midnight.with_timezone(&Utc));
+
+    // Servers deleted today still count.
+    let servers: Vec<i64> = servers_dsl::servers
+        .select(servers_dsl::id)
+        .filter(servers_dsl::owner_id.eq(owner))
+        .load(conn)
+        .await?;
+
+    let sessions: Vec<(DateTime<Utc>, Option<DateTime<Utc>>)> = sessions_dsl::server_sessions
+        .select((sessions_dsl::started_at, sessions_dsl::stopped_at))
+        .filter(sessions_dsl::server_id.eq_any(servers))
+        .filter(
+            sessions_dsl::stopped_at
+                .is_null()
+                .or(sessions_dsl::stopped_at.gt(midnight)),
+        )
+        .load(conn)
+        .await?;
+
+    let now = Utc::now();
+    Ok(sessions
+        .into_iter()
+        .map(|(started_at, stopped_at)| {
+            (stopped_at.unwrap_or(now) - started_at.max(midnight)).max(TimeDelta::zero())
+        })
+        .sum())
+}
+
+// A server asking for more memory than the quota could never be started.
+pub async fn check_create(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    owner: UserId,
+    memory: u64,
+) -> Result<(), ClientError> {
+    let quota = Quota::from_env()?;
+
+    if let Some(max_memory) = quota.max_memory
+        && memory > max_memory
+    {
+        return Err(ClientError::Other(format!(
+            "Ce serveur demande {}, plus que le quota de mémoire de {}.",
+            format_memory(memory),
+            format_memory(max_memory)
+        )));
+    }
+
+    let Some(max_servers) = quota.max_servers else {
+        return Ok(());
+    };
+
+    let usage = usage(conn, runtime, i64::try_from(owner.get())?, None).await?;
+    if usage.servers >= max_servers {
+        return Err(ClientError::Other(format!(
+            "Vous possédez déjà {}/{max_servers} serveurs.",
+            usage.servers
+        )));
+    }
+
+    Ok(())
+}
+
+// Quotas are charged to the owner of the server, whoever starts it.
+pub async fn check_start(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+) -> Result<(), ClientError> {
+    let owner: Option<i64> = servers_dsl::servers
+        .select(servers_dsl::owner_id)
+        .filter(servers_dsl::id.eq(id))
+        .get_result(conn)
+        .await?;
+    let Some(owner) = owner else {
+        return Ok(());
+    };
+
+    let quota = Quota::from_env()?;
+    let usage = usage(conn, runtime, owner, Some(id)).await?;
+
+    if let Some(max_running) = quota.max_running
+        && usage.running >= max_running
+    {
+        return Err(ClientError::Other(format!(
+            "Le propriétaire a déjà {}/{max_running} serveurs lancés.",
+            usage.running
+        )));
+    }
+
+    if let Some(max_memory) = quota.max_memory {
+        let requested = server_memory(id).await?;
+        if usage.memory + requested > max_memory {
+            return Err(ClientError::Other(format!(
+                "Quota de mémoire dépassé : {} demandés, {} déjà utilisés sur {}.",
+                format_memory(requested),
+                format_memory(usage.memory),
+                format_memory(max_memory)
+            )));
+        }
+    }
+
+    if let Some(hours) = quota.hours_per_day
+        && usage.today >= TimeDelta::hours(hours)
+    {
+        return Err(ClientError::Other(format!(
+            "Le propriétaire a déjà utilisé ses {hours} h de jeu aujourd'hui."
+        )));
+    }
+
+    Ok(())
+}
+
+// Opens a session when the server starts running and closes it when it stops, so that the
+// running time can be counted per day.
+pub async fn track_session(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    current: ServerStatus,
+    next: ServerStatus,
+) -> Result<(), ClientError> {
+    if current != ServerStatus::Running && next == ServerStatus::Running {
+        insert_into(sessions_dsl::server_sessions)
+            .values(sessions_dsl::server_id.eq(id))
+            .execute(conn)
+            .await?;
+    } else if current == ServerStatus::Running && next != ServerStatus::Running {
+        diesel::update(
+            sessions_dsl::server_sessions
+                .filter(sessions_dsl::server_id.eq(id))
+                .filter(sessions_dsl::stopped_at.is_null()),
+        )
+        .set(sessions_dsl::stopped_at.eq(diesel::dsl::now))
+        .execute(conn)
+        .await?;
+    }
+
+    Ok(())
+}
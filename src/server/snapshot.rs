@@ -0,0 +1,66 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::runtime::ContainerRuntime;
+use crate::server::archive::pack_worlds;
+use crate::server::console::run_command;
+use crate::server::lifecycle::{ServerStatus, check_container_stopped};
+use crate::util::world_dir;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use std::path::PathBuf;
+
+// Archives the worlds of a server into `dest`, with autosave paused while a running server is read.
+pub async fn snapshot(
+    conn: &mut PgPooled<'_>,
+    runtime: &dyn ContainerRuntime,
+    id: i64,
+    dest: PathBuf,
+) -> Result<u64, ClientError> {
+    let status: ServerStatus = servers_dsl::servers
+        .select(servers_dsl::status)
+        .filter(servers_dsl::id.eq(id))
+        .get_result(conn)
+        .await?;
+
+    let running = match status {
+        ServerStatus::Running => true,
+        ServerStatus::Stopped => false,
+        ServerStatus::Crashed => {
+            check_container_stopped(runtime, id).await?;
+            false
+        }
+        _ => {
+            return Err(ClientError::Other(format!(
+                "Impossible d'archiver le monde pendant : {}.",
+                status.label()
+            )));
+        }
+    };
+
+    if running {
+        run_command(conn, runtime, id, "save-off").await?;
+        if let Err(error) = run_command(conn, runtime, id, "save-all flush").await {
+            resume_saves(conn, runtime, id).await;
+            return Err(error);
+        }
+    }
+
+    let data_dir = world_dir(id).join("data");
+    let packed = tokio::task::spawn_blocking(move || pack_worlds(&data_dir, &dest))
+        .await
+        .map_err(|error| ClientError::Other(error.to_string()))
+        .and_then(|result| result);
+
+    if running {
+        resume_saves(conn, runtime, id).await;
+    }
+
+    packed
+}
+
+async fn resume_saves(conn: &mut PgPooled<'_>, runtime: &dyn ContainerRuntime, id: i64) {
+    if let Err(error) = run_command(conn, runtime, id, "save-on").await {
+        log::error!("Cannot re-enable autosave on server {id}: {error}");
+    }
+}
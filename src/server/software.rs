@@ -0,0 +1,167 @@
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Text;
+use std::io::Write;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub enum ServerType {
+    Vanilla,
+    Paper,
+    Purpur,
+    Spigot,
+    Fabric,
+    Forge,
+    NeoForge,
+}
+
+impl ServerType {
+    pub const ALL: [Self; 7] = [
+        Self::Vanilla,
+        Self::Paper,
+        Self::Purpur,
+        Self::Spigot,
+        Self::Fabric,
+        Self::Forge,
+        Self::NeoForge,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Vanilla => "vanilla",
+            Self::Paper => "paper",
+            Self::Purpur => "purpur",
+            Self::Spigot => "spigot",
+            Self::Fabric => "fabric",
+            Self::Forge => "forge",
+            Self::NeoForge => "neoforge",
+        }
+    }
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Vanilla => "Vanilla",
+            Self::Paper => "Paper",
+            Self::Purpur => "Purpur",
+            Self::Spigot => "Spigot",
+            Self::Fabric => "Fabric",
+            Self::Forge => "Forge",
+            Self::NeoForge => "NeoForge",
+        }
+    }
+
+    // Value of the `TYPE` variable understood by the itzg/minecraft-server image.
+    #[must_use]
+    pub const fn env_value(self) -> &'static str {
+        match self {
+            Self::Vanilla => "VANILLA",
+            Self::Paper => "PAPER",
+            Self::Purpur => "PURPUR",
+            Self::Spigot => "SPIGOT",
+            Self::Fabric => "FABRIC",
+            Self::Forge => "FORGE",
+            Self::NeoForge => "NEOFORGE",
+        }
+    }
+
+    #[must_use]
+    pub const fn min_version(self) -> Option<&'static str> {
+        match self {
+            Self::Vanilla | Self::Paper | Self::Spigot | Self::Forge => None,
+            Self::Purpur => Some("1.14.1"),
+            Self::Fabric => Some("1.14"),
+            Self::NeoForge => Some("1.20.1"),
+        }
+    }
+
+    #[must_use]
+    pub fn supports_version(self, version: &str) -> bool {
+        self.min_version().is_none_or(|min| {
+            parse_version(version).is_some_and(|version| Some(version) >= parse_version(min))
+        })
+    }
+}
+
+// Pre-releases (`1.21-rc1`) compare as their release, weekly snapshots (`24w14a`) do not compare.
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    version
+        .split_once('-')
+        .map_or(version, |(release, _)| release)
+        .split('.')
+        .map(|part| part.parse::<u32>().ok())
+        .collect()
+}
+
+impl FromStr for ServerType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|server_type| server_type.as_str() == value)
+            .ok_or_else(|| format!("Unknown server type `{value}`"))
+    }
+}
+
+impl ToSql<Text, Pg> for ServerType {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for ServerType {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        Ok(<String as FromSql<Text, Pg>>::from_sql(bytes)?.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(parse_version("1.21"), Some(vec![1, 21]));
+        assert_eq!(parse_version("1.20.1"), Some(vec![1, 20, 1]));
+        assert_eq!(parse_version("1.21-rc1"), Some(vec![1, 21]));
+        assert_eq!(parse_version("1.20.5-pre1"), Some(vec![1, 20, 5]));
+        assert_eq!(parse_version("24w14a"), None);
+        assert_eq!(parse_version("latest"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn minimum_versions() {
+        let cases = [
+            (ServerType::Vanilla, "1.8.9", true),
+            (ServerType::Vanilla, "24w14a", true),
+            (ServerType::Forge, "1.7.10", true),
+            (ServerType::Fabric, "1.13.2", false),
+            (ServerType::Fabric, "1.14", true),
+            (ServerType::Fabric, "1.14-pre1", true),
+            (ServerType::Fabric, "1.21.8", true),
+            (ServerType::Fabric, "24w14a", false),
+            (ServerType::Purpur, "1.14", false),
+            (ServerType::Purpur, "1.14.1", true),
+            (ServerType::Purpur, "1.20", true),
+            (ServerType::NeoForge, "1.20", false),
+            (ServerType::NeoForge, "1.20.1", true),
+            (ServerType::NeoForge, "1.21-rc1", true),
+            (ServerType::NeoForge, "1.9.4", false),
+        ];
+
+        for (server_type, version, supported) in cases {
+            assert_eq!(
+                server_type.supports_version(version),
+                supported,
+                "{server_type:?} {version}"
+            );
+        }
+    }
+}
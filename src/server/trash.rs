@@ -0,0 +1,143 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::backup::backup_dir;
+use crate::util::{parse_duration, world_dir};
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::dsl::now;
+use diesel::{ExpressionMethods, NullableExpressionMethods, QueryDsl, delete, update};
+use diesel_async::RunQueryDsl;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+#[must_use]
+pub fn trash_dir(id: i64) -> PathBuf {
+    Path::new(&std::env::var("TRASH_DIR").unwrap_or_else(|_| "trash".to_owned()))
+        .join(id.to_string())
+}
+
+pub fn retention() -> Result<TimeDelta, ClientError> {
+    let retention = std::env::var("TRASH_RETENTION")
+        .map_or_else(|_| parse_duration("7d"), |value| parse_duration(&value))?;
+    TimeDelta::from_std(retention).map_err(|error| ClientError::Other(error.to_string()))
+}
+
+#[must_use]
+pub fn purge_date(deleted_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    retention().ok().map(|retention| deleted_at + retention)
+}
+
+async fn move_dir(from: &Path, to: &Path) -> Result<(), ClientError> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    match fs::rename(from, to).await {
+        // The trash may be mounted on another volume than the worlds.
+        Err(error) if error.kind() == ErrorKind::CrossesDevices => {
+            let (from, to) = (from.to_path_buf(), to.to_path_buf());
+            tokio::task::spawn_blocking(move || {
+                if to.try_exists()? {
+                    return Err(ErrorKind::AlreadyExists.into());
+                }
+                if let Err(error) = copy_dir(&from, &to) {
+                    let _ = std::fs::remove_dir_all(&to);
+                    return Err(error);
+                }
+                std::fs::remove_dir_all(&from)
+            })
+            .await
+            .map_err(|error| ClientError::Other(error.to_string()))??;
+            Ok(())
+        }
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let kind = entry.file_type()?;
+        if kind.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if kind.is_symlink() {
+            std::os::unix::fs::symlink(std::fs::read_link(entry.path())?, target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+async fn remove_dir(dir: &Path) -> Result<(), ClientError> {
+    match fs::remove_dir_all(dir).await {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+// The backups stay where they are, they are only removed by the purge.
+pub async fn trash_server(conn: &mut PgPooled<'_>, id: i64) -> Result<DateTime<Utc>, ClientError> {
+    move_dir(&world_dir(id), &trash_dir(id)).await?;
+
+    Ok(update(servers_dsl::servers.filter(servers_dsl::id.eq(id)))
+        .set(servers_dsl::deleted_at.eq(now))
+        .returning(servers_dsl::deleted_at.assume_not_null())
+        .get_result(conn)
+        .await?)
+}
+
+pub async fn untrash_server(conn: &mut PgPooled<'_>, id: i64) -> Result<(), ClientError> {
+    move_dir(&trash_dir(id), &world_dir(id)).await?;
+
+    update(servers_dsl::servers.filter(servers_dsl::id.eq(id)))
+        .set(servers_dsl::deleted_at.eq(None::<DateTime<Utc>>))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn purge_server(conn: &mut PgPooled<'_>, id: i64) -> Result<(), ClientError> {
+    remove_dir(&trash_dir(id)).await?;
+    remove_dir(&backup_dir(id)).await?;
+
+    delete(servers_dsl::servers.filter(servers_dsl::id.eq(id)))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_directories() {
+        let dir = std::env::temp_dir().join(format!("trash-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let from = dir.join("from");
+        std::fs::create_dir_all(from.join("data/world/region")).unwrap();
+        std::fs::write(from.join("data/world/level.dat"), "level").unwrap();
+        std::fs::write(from.join("docker-compose.yml"), "services:").unwrap();
+        std::os::unix::fs::symlink("data/world", from.join("link")).unwrap();
+
+        let to = dir.join("to");
+        copy_dir(&from, &to).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(to.join("data/world/level.dat")).unwrap(),
+            "level"
+        );
+        assert!(to.join("data/world/region").is_dir());
+        assert_eq!(
+            std::fs::read_link(to.join("link")).unwrap(),
+            Path::new("data/world")
+        );
+        assert!(copy_dir(&from, &to).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
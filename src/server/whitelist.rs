@@ -0,0 +1,45 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::PgPooled;
+use crate::database::schemas::server_whitelist::dsl as whitelist_dsl;
+use crate::server::compose::synchronize_file;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use std::collections::BTreeMap;
+
+#[must_use]
+pub fn is_valid_player(player: &str) -> bool {
+    (3..=16).contains(&player.len())
+        && player
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+pub async fn players(conn: &mut PgPooled<'_>, id: i64) -> Result<Vec<String>, ClientError> {
+    Ok(whitelist_dsl::server_whitelist
+        .select(whitelist_dsl::player)
+        .filter(whitelist_dsl::server_id.eq(id))
+        .order_by(whitelist_dsl::player)
+        .load(conn)
+        .await?)
+}
+
+// An empty whitelist leaves the server open to everyone.
+pub async fn apply(
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    environment: &mut BTreeMap<String, String>,
+) -> Result<(), ClientError> {
+    let players = players(conn, id).await?;
+    let enabled = if players.is_empty() { "FALSE" } else { "TRUE" };
+
+    if players.is_empty() {
+        environment.remove("WHITELIST");
+    } else {
+        environment.insert("WHITELIST".to_owned(), players.join(","));
+    }
+    environment.insert("ENABLE_WHITELIST".to_owned(), enabled.to_owned());
+    environment.insert("ENFORCE_WHITELIST".to_owned(), enabled.to_owned());
+    synchronize_file(environment, "WHITELIST");
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::backup::{BackupKind, create_backup};
+use crate::server::lifecycle::ServerStatus;
+use crate::server::lock::get_locks_from_ctx;
+use crate::util::{get_pool_from_ctx, get_runtime_from_ctx, notify};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::Context;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+pub async fn run(ctx: Context, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately, the bot may just have been restarted.
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        if let Err(error) = backup_all(&ctx).await {
+            log::error!("Scheduled backups failed: {error}");
+        }
+    }
+}
+
+// Only running servers are backed up, a stopped world does not change.
+async fn backup_all(ctx: &Context) -> Result<(), ClientError> {
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+    let runtime = get_runtime_from_ctx(ctx).await?;
+    let locks = get_locks_from_ctx(ctx).await?;
+
+    let running: Vec<(i64, String)> = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::name))
+        .filter(servers_dsl::status.eq(ServerStatus::Running))
+        .load(&mut conn)
+        .await?;
+
+    for (id, name) in running {
+        let Some(_guard) = locks.try_lock(id) else {
+            log::info!("Skipping the backup of {name}, an operation is in progress");
+            continue;
+        };
+
+        if let Err(error) =
+            create_backup(&mut conn, runtime.as_ref(), id, BackupKind::Scheduled).await
+        {
+            log::error!("Unable to back up server {name}: {error}");
+            notify(
+                ctx,
+                format!("**La sauvegarde de ``{name}`` a échoué :**\n{error}"),
+            )
+            .await;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::protocol::slp;
+use crate::server::lifecycle::{ServerStatus, halt, transition};
+use crate::server::lock::lock_server;
+use crate::util::{get_pool_from_ctx, get_runtime_from_ctx, mc_host, notify};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::Context;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const CHECK_INTERVAL: Duration = Duration::from_mins(1);
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub async fn run(ctx: Context, timeout: Duration) {
+    let mut idle_since: HashMap<i64, Instant> = HashMap::new();
+
+    let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if let Err(error) = check(&ctx, timeout, &mut idle_since).await {
+            log::error!("Idle check failed: {error}");
+        }
+    }
+}
+
+async fn check(
+    ctx: &Context,
+    timeout: Duration,
+    idle_since: &mut HashMap<i64, Instant>,
+) -> Result<(), ClientError> {
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let running: Vec<(i64, String, i64)> = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::name, servers_dsl::port))
+        .filter(servers_dsl::status.eq(ServerStatus::Running))
+        .load(&mut conn)
+        .await?;
+
+    idle_since.retain(|id, _| running.iter().any(|(running_id, _, _)| running_id == id));
+
+    let host = mc_host()?;
+    for (id, name, port) in running {
+        // An unreachable server is still booting or broken, neither means nobody plays on it.
+        let online = match slp::status(&host, u16::try_from(port)?, PING_TIMEOUT).await {
+            Ok(status) => status.players.online,
+            Err(error) => {
+                log::debug!("Unable to ping server {name}: {error}");
+                idle_since.remove(&id);
+                continue;
+            }
+        };
+
+        if online > 0 {
+            idle_since.remove(&id);
+            continue;
+        }
+
+        let since = *idle_since.entry(id).or_insert_with(Instant::now);
+        if since.elapsed() < timeout {
+            continue;
+        }
+        idle_since.remove(&id);
+
+        if let Err(error) = stop_idle(ctx, &mut conn, id, &name, timeout).await {
+            log::error!("Unable to stop idle server {name}: {error}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn stop_idle(
+    ctx: &Context,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    name: &str,
+    timeout: Duration,
+) -> Result<(), ClientError> {
+    let _guard = lock_server(ctx, id).await?;
+
+    transition(conn, id, ServerStatus::Stopping).await?;
+    halt(
+        conn,
+        get_runtime_from_ctx(ctx).await?.as_ref(),
+        id,
+        ServerStatus::Stopped,
+    )
+    .await?;
+
+    log::info!("server stoped : {name} (idle)!");
+
+    notify(
+        ctx,
+        format!(
+            "**Serveur ``{name}`` arrêté après {} minutes sans joueur.**",
+            timeout.as_secs() / 60
+        ),
+    )
+    .await;
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::util::{parse_duration, parse_key};
+use serenity::all::Context;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+pub mod backup;
+pub mod idle;
+pub mod purge;
+pub mod quota;
+pub mod reconcile;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+// `ready` is emitted again after every gateway reconnection.
+pub fn spawn_all(ctx: &Context) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tokio::spawn(reconcile::run(ctx.clone()));
+    tokio::spawn(purge::run(ctx.clone()));
+
+    if let Ok(interval) = std::env::var("BACKUP_INTERVAL") {
+        match parse_duration(&interval) {
+            Ok(interval) => {
+                tokio::spawn(backup::run(ctx.clone(), interval));
+            }
+            Err(error) => log::error!("Scheduled backups disabled: {error}"),
+        }
+    }
+
+    if std::env::var("IDLE_TIMEOUT_MINUTES").is_ok() {
+        match parse_key::<u64>("IDLE_TIMEOUT_MINUTES") {
+            Ok(minutes) => {
+                tokio::spawn(idle::run(ctx.clone(), Duration::from_mins(minutes)));
+            }
+            Err(error) => log::error!("Idle watcher disabled: {error}"),
+        }
+    }
+
+    if std::env::var("QUOTA_HOURS_PER_DAY").is_ok() {
+        match parse_key::<i64>("QUOTA_HOURS_PER_DAY") {
+            Ok(hours) => {
+                tokio::spawn(quota::run(ctx.clone(), hours));
+            }
+            Err(error) => log::error!("Daily quota watcher disabled: {error}"),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::trash::{purge_server, retention};
+use crate::util::get_pool_from_ctx;
+use chrono::Utc;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::Context;
+use std::time::Duration;
+
+const CHECK_INTERVAL: Duration = Duration::from_hours(1);
+
+pub async fn run(ctx: Context) {
+    let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if let Err(error) = purge(&ctx).await {
+            log::error!("Trash purge failed: {error}");
+        }
+    }
+}
+
+async fn purge(ctx: &Context) -> Result<(), ClientError> {
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let expired: Vec<(i64, String)> = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::name))
+        .filter(servers_dsl::deleted_at.lt(Utc::now() - retention()?))
+        .load(&mut conn)
+        .await?;
+
+    for (id, name) in expired {
+        match purge_server(&mut conn, id).await {
+            Ok(()) => log::info!("Purged server {name} ({id}) from the trash"),
+            Err(error) => log::error!("Unable to purge server {name} ({id}): {error}"),
+        }
+    }
+
+    Ok(())
+}
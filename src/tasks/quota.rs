@@ -0,0 +1,87 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::lifecycle::{ServerStatus, halt, transition};
+use crate::server::lock::lock_server;
+use crate::server::quota::played_today;
+use crate::util::{get_pool_from_ctx, get_runtime_from_ctx, notify};
+use chrono::TimeDelta;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::Context;
+use std::collections::HashSet;
+use std::time::Duration;
+
+const CHECK_INTERVAL: Duration = Duration::from_mins(1);
+
+// The daily hours are checked at start too, this stops the servers that run past them.
+pub async fn run(ctx: Context, hours: i64) {
+    let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if let Err(error) = check(&ctx, hours).await {
+            log::error!("Quota check failed: {error}");
+        }
+    }
+}
+
+async fn check(ctx: &Context, hours: i64) -> Result<(), ClientError> {
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+
+    let running: Vec<(i64, String, Option<i64>)> = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::name, servers_dsl::owner_id))
+        .filter(servers_dsl::status.eq(ServerStatus::Running))
+        .load(&mut conn)
+        .await?;
+
+    let owners: HashSet<i64> = running.iter().filter_map(|(_, _, owner)| *owner).collect();
+    let mut exhausted = HashSet::new();
+    for owner in owners {
+        if played_today(&mut conn, owner).await? >= TimeDelta::hours(hours) {
+            exhausted.insert(owner);
+        }
+    }
+
+    for (id, name, owner) in running {
+        if !owner.is_some_and(|owner| exhausted.contains(&owner)) {
+            continue;
+        }
+        if let Err(error) = stop_exhausted(ctx, &mut conn, id, &name, hours).await {
+            log::error!("Unable to stop server {name} over its quota: {error}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn stop_exhausted(
+    ctx: &Context,
+    conn: &mut PgPooled<'_>,
+    id: i64,
+    name: &str,
+    hours: i64,
+) -> Result<(), ClientError> {
+    let _guard = lock_server(ctx, id).await?;
+
+    transition(conn, id, ServerStatus::Stopping).await?;
+    halt(
+        conn,
+        get_runtime_from_ctx(ctx).await?.as_ref(),
+        id,
+        ServerStatus::Stopped,
+    )
+    .await?;
+
+    log::info!("server stoped : {name} (daily quota)!");
+
+    notify(
+        ctx,
+        format!(
+            "**Serveur ``{name}`` arrêté : son propriétaire a utilisé ses {hours} h de jeu aujourd'hui.**"
+        ),
+    )
+    .await;
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use crate::client::error::ClientError;
+use crate::database::postgresql::{PgPool, PgPooled};
+use crate::database::schemas::servers::dsl as servers_dsl;
+use crate::server::lifecycle::{ServerStatus, compare_and_set, reconciled};
+use crate::server::lock::get_locks_from_ctx;
+use crate::util::{get_pool_from_ctx, get_runtime_from_ctx, notify, parse_duration};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serenity::all::Context;
+
+pub async fn run(ctx: Context) {
+    let interval = std::env::var("RECONCILE_INTERVAL")
+        .map_or_else(|_| parse_duration("5m"), |value| parse_duration(&value))
+        .unwrap_or_else(|error| {
+            log::error!("{error}");
+            std::process::exit(1);
+        });
+
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        if let Err(error) = reconcile(&ctx).await {
+            log::error!("Reconciliation failed: {error}");
+        }
+    }
+}
+
+pub async fn reconcile(ctx: &Context) -> Result<(), ClientError> {
+    let pool: PgPool = get_pool_from_ctx(ctx).await?;
+    let mut conn: PgPooled = pool.get().await?;
+    let runtime = get_runtime_from_ctx(ctx).await?;
+    let locks = get_locks_from_ctx(ctx).await?;
+
+    let servers: Vec<(i64, String, ServerStatus)> = servers_dsl::servers
+        .select((servers_dsl::id, servers_dsl::name, servers_dsl::status))
+        .filter(servers_dsl::deleted_at.is_null())
+        .order_by(servers_dsl::name)
+        .load(&mut conn)
+        .await?;
+
+    let mut fixed = Vec::new();
+    for (id, name, status) in servers {
+        // A command is working on this server, its state is expected to be transient.
+        let Some(_guard) = locks.try_lock(id) else {
+            continue;
+        };
+
+        let container = match runtime.status(id).await {
+            Ok(container) => container,
+            Err(error) => {
+                log::error!("Unable to inspect server {name}: {error}");
+                continue;
+            }
+        };
+        let Some(next) = reconciled(status, container) else {
+            continue;
+        };
+
+        if compare_and_set(&mut conn, id, status, next).await? {
+            log::warn!(
+                "Server {name} was {} but its container is {container:?}, now {}.",
+                status.as_str(),
+                next.as_str()
+            );
+            fixed.push(format!(
+                "* ``{name}`` : {} → {}",
+                status.label(),
+                next.label()
+            ));
+        }
+    }
+
+    if !fixed.is_empty() {
+        notify(
+            ctx,
+            format!("**État des serveurs corrigé :**\n{}", fixed.join("\n")),
+        )
+        .await;
+    }
+
+    Ok(())
+}
@@ -1,8 +1,12 @@
-use crate::client::data::PgPoolData;
+use crate::client::data::{PgPoolData, RuntimeData};
 use crate::client::error::ClientError;
 use crate::database::postgresql::PgPool;
-use serenity::all::Context;
+use crate::runtime::ContainerRuntime;
+use serenity::all::{ChannelId, Context, CreateEmbed, CreateMessage};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::signal::unix::SignalKind;
 
 pub mod logger;
@@ -53,6 +57,126 @@ where
     })
 }
 
+pub fn parse_duration(value: &str) -> Result<Duration, ClientError> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let amount = amount
+        .parse::<u64>()
+        .map_err(|error| ClientError::Other(format!("Invalid duration `{value}`: {error}")))?;
+    let factor = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => {
+            return Err(ClientError::Other(format!(
+                "Invalid duration unit in `{value}`."
+            )));
+        }
+    };
+
+    Ok(Duration::from_secs(amount * factor))
+}
+
+pub fn parse_memory(value: &str) -> Result<u64, ClientError> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let amount = amount
+        .parse::<u64>()
+        .map_err(|error| ClientError::Other(format!("Invalid memory size `{value}`: {error}")))?;
+    let factor: u64 = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        _ => {
+            return Err(ClientError::Other(format!(
+                "Invalid memory unit in `{value}`."
+            )));
+        }
+    };
+
+    Ok(amount * factor)
+}
+
+#[must_use]
+pub fn format_memory(bytes: u64) -> String {
+    if bytes.is_multiple_of(1 << 30) {
+        format!("{}G", bytes >> 30)
+    } else {
+        format!("{}M", bytes >> 20)
+    }
+}
+
+#[must_use]
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["o", "Ko", "Mo", "Go"];
+
+    let mut size = bytes;
+    let mut unit = 0;
+    while size >= 1024 * 10 && unit < UNITS.len() - 1 {
+        size /= 1024;
+        unit += 1;
+    }
+    format!("{size} {}", UNITS[unit])
+}
+
+// Address the bot reaches the Minecraft servers on, which may differ from the public `IP`.
+pub fn mc_host() -> Result<String, ClientError> {
+    parse_key::<String>("MC_HOST").or_else(|_| parse_key::<String>("IP"))
+}
+
+// Address RCON is published on: it gives full control of the server, so it stays off the public
+// interfaces unless configured otherwise.
+pub fn rcon_bind() -> Result<String, ClientError> {
+    std::env::var("RCON_BIND")
+        .map_or_else(|_| Ok("127.0.0.1".to_owned()), |_| parse_key("RCON_BIND"))
+}
+
+#[must_use]
+pub fn truncate(text: &str, max_length: usize) -> String {
+    if text.len() <= max_length {
+        return text.to_owned();
+    }
+
+    let mut end = max_length;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…", &text[..end])
+}
+
+#[must_use]
+pub fn truncate_start(text: &str, max_length: usize) -> String {
+    if text.len() <= max_length {
+        return text.to_owned();
+    }
+
+    let mut start = text.len() - max_length;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("…{}", &text[start..])
+}
+
+#[must_use]
+pub fn redact(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(text.to_owned(), |text, secret| {
+            text.replace(secret.as_str(), "[secret]")
+        })
+}
+
 pub fn get_time() -> Result<u64, ClientError> {
     Ok(std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -72,3 +196,34 @@ pub async fn get_pool_from_ctx(ctx: &Context) -> Result<PgPool, ClientError> {
         .ok_or(ClientError::OtherStatic("Missing PgPoolData in data."))
         .cloned()
 }
+
+pub async fn get_runtime_from_ctx(ctx: &Context) -> Result<Arc<dyn ContainerRuntime>, ClientError> {
+    ctx.data
+        .read()
+        .await
+        .get::<RuntimeData>()
+        .ok_or(ClientError::OtherStatic("Missing RuntimeData in data."))
+        .cloned()
+}
+
+#[must_use]
+pub fn world_dir(id: i64) -> PathBuf {
+    Path::new("worlds").join(id.to_string())
+}
+
+pub async fn notify(ctx: &Context, description: String) {
+    let Ok(channel_id) = parse_key::<u64>("NOTIFY_CHANNEL_ID") else {
+        return;
+    };
+
+    let embed = CreateEmbed::new()
+        .description(description)
+        .color(EMBED_COLOR);
+
+    if let Err(error) = ChannelId::new(channel_id)
+        .send_message(&ctx.http, CreateMessage::new().embed(embed))
+        .await
+    {
+        log::error!("Unable to send notification: {error}");
+    }
+}